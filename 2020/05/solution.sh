@@ -1,4 +0,0 @@
-#!/usr/bin/env bash
-
-cd ../aoc_rust/
-cargo run -- -d 05 -i ../05/input
@@ -1,4 +0,0 @@
-#!/usr/bin/env bash
-
-cd ../aoc_rust/
-cargo run -- -d 06 -i ../06/input
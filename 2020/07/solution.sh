@@ -1,4 +0,0 @@
-#!/usr/bin/env bash
-
-cd ../aoc_rust/
-cargo run -- -d 07 -i ../07/input
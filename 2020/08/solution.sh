@@ -1,4 +0,0 @@
-#!/usr/bin/env bash
-
-cd ../aoc_rust/
-cargo run -- -d 08 -i ../08/input
@@ -0,0 +1,108 @@
+extern crate regex;
+
+use regex::Regex;
+use aoc_core::{AoCError, Answer, Solution, parse};
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 2;
+
+    type Input = Vec<PasswordEntry>;
+
+    fn parse(&self, input: &str) -> Result<Vec<PasswordEntry>, AoCError> {
+        parse::lines(input)
+            .map(|(i, line)| PasswordEntry::new(line.trim_end(), i))
+            .collect()
+    }
+
+    fn part1(&self, entries: &Vec<PasswordEntry>) -> Result<Answer, AoCError> {
+        Ok(entries.iter()
+            .filter(|entry| entry.policy.allows_by_count(&entry.password))
+            .count()
+            .into())
+    }
+
+    fn part2(&self, entries: &Vec<PasswordEntry>) -> Result<Answer, AoCError> {
+        Ok(entries.iter()
+            .filter(|entry| entry.policy.allows_by_position(&entry.password))
+            .count()
+            .into())
+    }
+}
+
+/// A line like "1-3 a: abcde".
+pub struct PasswordEntry {
+    policy: Policy,
+    password: String,
+}
+
+struct Policy {
+    low: usize,
+    high: usize,
+    letter: char,
+}
+
+impl PasswordEntry {
+    fn new(line: &str, line_number: usize) -> Result<PasswordEntry, AoCError> {
+        lazy_static! {
+            static ref ENTRY: Regex = Regex::new(r"(?x)
+                ^(?P<low>\d+)-(?P<high>\d+)\s  # the range, e.g. 1-3
+                (?P<letter>[a-z]):\s
+                (?P<password>.*)$
+            ").unwrap();
+        }
+        let cap = ENTRY.captures(line).ok_or_else(|| AoCError::parse(
+            line_number, 1, "Expected \"<low>-<high> <letter>: <password>\""
+        ))?;
+        let low = cap.name("low").unwrap();
+        let high = cap.name("high").unwrap();
+        let policy = Policy {
+            low: parse::value(low.as_str(), line_number, low.start() + 1)?,
+            high: parse::value(high.as_str(), line_number, high.start() + 1)?,
+            letter: cap["letter"].chars().next().unwrap(),
+        };
+        if policy.low == 0 || policy.low > policy.high {
+            return Err(AoCError::parse(
+                line_number, 1, "Expected a range 1 <= low <= high"
+            ));
+        }
+        Ok(PasswordEntry { policy, password: cap["password"].to_string() })
+    }
+}
+
+impl Policy {
+    /// The letter must occur `low` to `high` times.
+    fn allows_by_count(&self, password: &str) -> bool {
+        let count = password.chars().filter(|&c| c == self.letter).count();
+        self.low <= count && count <= self.high
+    }
+
+    /// The letter must occur at exactly one of the 1-based positions `low`
+    /// and `high`.
+    fn allows_by_position(&self, password: &str) -> bool {
+        let at = |pos: usize| password.chars().nth(pos - 1) == Some(self.letter);
+        at(self.low) != at(self.high)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policies() {
+        let entries = Day02.parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n")
+            .unwrap();
+        let by_count: Vec<bool> = entries.iter()
+            .map(|entry| entry.policy.allows_by_count(&entry.password))
+            .collect();
+        let by_position: Vec<bool> = entries.iter()
+            .map(|entry| entry.policy.allows_by_position(&entry.password))
+            .collect();
+        assert_eq!(by_count, vec![true, false, true]);
+        assert_eq!(by_position, vec![true, false, false]);
+        assert!(Day02.parse("3-1 a: abc").is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use aoc_core::{AoCError, Answer, Grid, Solution};
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 3;
+
+    /// `true` for trees, `false` for open squares.
+    type Input = Grid<bool>;
+
+    fn parse(&self, input: &str) -> Result<Grid<bool>, AoCError> {
+        Grid::parse_char_map(input, |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+    }
+
+    fn part1(&self, map: &Grid<bool>) -> Result<Answer, AoCError> {
+        Ok(count_trees(map, 3, 1).into())
+    }
+
+    fn part2(&self, map: &Grid<bool>) -> Result<Answer, AoCError> {
+        let slopes: [(usize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+        Ok(slopes.iter()
+            .map(|&(right, down)| count_trees(map, right, down))
+            .product::<usize>()
+            .into())
+    }
+}
+
+/// Counts the trees on the way from the top left to the bottom of the
+/// map. The map repeats to the right indefinitely.
+fn count_trees(map: &Grid<bool>, right: usize, down: usize) -> usize {
+    (0..map.height()).step_by(down)
+        .enumerate()
+        .filter(|&(step, y)| map.get((step * right) % map.width(), y) == Some(&true))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_trees() {
+        let map = Day03.parse("\
+            ..##.......\n\
+            #...#...#..\n\
+            .#....#..#.\n\
+            ..#.#...#.#\n\
+            .#...##..#.\n\
+            ..#.##.....\n\
+            .#.#.#....#\n\
+            .#........#\n\
+            #.##...#...\n\
+            #...##....#\n\
+            .#..#...#.#\n"
+        ).unwrap();
+        assert_eq!(count_trees(&map, 3, 1), 7);
+        assert_eq!(count_trees(&map, 1, 2), 2);
+        assert_eq!(Day03.part2(&map), Ok(Answer::Unsigned(336)));
+    }
+}
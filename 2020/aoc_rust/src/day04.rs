@@ -0,0 +1,159 @@
+extern crate regex;
+
+use std::collections::HashMap;
+use regex::Regex;
+use aoc_core::{AoCError, Answer, Solution, parse};
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 4;
+
+    /// One map from field name to value per passport.
+    type Input = Vec<HashMap<String, String>>;
+
+    fn parse(&self, input: &str) -> Result<Vec<HashMap<String, String>>, AoCError> {
+        parse::numbered_blocks(input).iter()
+            .map(|block| parse_passport(block))
+            .collect()
+    }
+
+    fn part1(
+        &self,
+        passports: &Vec<HashMap<String, String>>
+    ) -> Result<Answer, AoCError> {
+        Ok(passports.iter()
+            .filter(|passport| has_required_fields(passport))
+            .count()
+            .into())
+    }
+
+    fn part2(
+        &self,
+        passports: &Vec<HashMap<String, String>>
+    ) -> Result<Answer, AoCError> {
+        Ok(passports.iter()
+            .filter(|passport| has_required_fields(passport))
+            .filter(|passport| passport.iter().all(|(key, value)| {
+                (field_validator(key).unwrap())(value)
+            }))
+            .count()
+            .into())
+    }
+}
+
+/// All fields except for "cid" are required.
+const REQUIRED_FIELDS: [&str; 7] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+
+/// Returns the validator for a field, or `None` for unknown fields.
+fn field_validator(key: &str) -> Option<fn(&str) -> bool> {
+    lazy_static! {
+        static ref HEIGHT: Regex = Regex::new(
+            r"^(?P<hgt>\d{1,3})(?P<unit>in|cm)$"
+        ).unwrap();
+        static ref HAIR_COLOR: Regex = Regex::new(r"^#[a-f0-9]{6}$").unwrap();
+        static ref PASSPORT_ID: Regex = Regex::new(r"^\d{9}$").unwrap();
+    }
+
+    fn year_between(value: &str, from: u32, to: u32) -> bool {
+        value.len() == 4 && value.parse::<u32>().is_ok_and(|y| from <= y && y <= to)
+    }
+
+    let validator: fn(&str) -> bool = match key {
+        "byr" => |v| year_between(v, 1920, 2002),
+        "iyr" => |v| year_between(v, 2010, 2020),
+        "eyr" => |v| year_between(v, 2020, 2030),
+        "hgt" => |v| match HEIGHT.captures(v) {
+            Some(cap) => {
+                let hgt: u32 = cap["hgt"].parse().unwrap();
+                match &cap["unit"] {
+                    "cm" => (150..=193).contains(&hgt),
+                    _ => (59..=76).contains(&hgt),
+                }
+            },
+            None => false,
+        },
+        "hcl" => |v| HAIR_COLOR.is_match(v),
+        "ecl" => |v| ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"].contains(&v),
+        "pid" => |v| PASSPORT_ID.is_match(v),
+        "cid" => |_| true,
+        _ => return None,
+    };
+    Some(validator)
+}
+
+fn has_required_fields(passport: &HashMap<String, String>) -> bool {
+    REQUIRED_FIELDS.iter().all(|key| passport.contains_key(*key))
+}
+
+/// Parses the "key:value" pairs of a block of numbered lines.
+fn parse_passport(block: &[(usize, &str)]) -> Result<HashMap<String, String>, AoCError> {
+    let mut passport: HashMap<String, String> = HashMap::new();
+    for &(i, line) in block {
+        let mut column = 1;
+        for item in line.split(' ') {
+            if !item.is_empty() {
+                let (key, value) = item.split_once(':').ok_or_else(|| {
+                    AoCError::parse(i, column, "Expected \"<key>:<value>\"")
+                })?;
+                if field_validator(key).is_none() {
+                    return Err(AoCError::parse(
+                        i, column, format!("Unknown field \"{}\"", key)
+                    ));
+                }
+                if passport.insert(key.to_string(), value.to_string()).is_some() {
+                    return Err(AoCError::parse(
+                        i, column, format!("Duplicate field \"{}\"", key)
+                    ));
+                }
+            }
+            column += item.chars().count() + 1;
+        }
+    }
+    Ok(passport)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_validators() {
+        let valid = |key: &str, value: &str| (field_validator(key).unwrap())(value);
+        assert!(valid("byr", "2002"));
+        assert!(!valid("byr", "2003"));
+        assert!(valid("hgt", "60in"));
+        assert!(valid("hgt", "190cm"));
+        assert!(!valid("hgt", "190in"));
+        assert!(!valid("hgt", "190"));
+        assert!(valid("hcl", "#123abc"));
+        assert!(!valid("hcl", "#123abz"));
+        assert!(!valid("hcl", "123abc"));
+        assert!(valid("ecl", "brn"));
+        assert!(!valid("ecl", "wat"));
+        assert!(valid("pid", "000000001"));
+        assert!(!valid("pid", "0123456789"));
+        assert!(field_validator("xyz").is_none());
+    }
+
+    #[test]
+    fn test_passports() {
+        let passports = Day04.parse("\
+            eyr:1972 cid:100\n\
+            hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926\n\
+            \n\
+            pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\n\
+            hcl:#623a2f\n\
+            \n\
+            hcl:#cfa07d eyr:2025 pid:166559648\n\
+            iyr:2011 ecl:brn hgt:59in\n"
+        ).unwrap();
+        assert_eq!(Day04.part1(&passports), Ok(Answer::Unsigned(2)));
+        assert_eq!(Day04.part2(&passports), Ok(Answer::Unsigned(1)));
+        assert_eq!(
+            Day04.parse("byr:1980\nfoo:bar"),
+            Err(AoCError::parse(2, 1, "Unknown field \"foo\""))
+        );
+    }
+}
@@ -1,75 +1,83 @@
-use crate::fileutils::{self};
+use aoc_core::{AoCError, Answer, Solution, parse};
 
-pub fn run_day05(puzzle_input: &str) {
-    match fileutils::read_lines(puzzle_input) {
-        Ok(lines) => {
-            let mut max_id = 0;
-            let mut ids = Vec::new();
-            for line in lines {
-                if let Ok(boarding_pass) = line {
-                    let seat = PlaneSeat::new(&boarding_pass);
-                    max_id = std::cmp::max(max_id, seat.id);
-                    ids.push(seat.id);
-                    println!("boarding pass: {}, {:?}", boarding_pass, seat);
-                }
-            }
-            println!("Maximum seat ID: {}", max_id);
+pub struct Day05;
+
+impl Solution for Day05 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 5;
+
+    type Input = Vec<PlaneSeat>;
+
+    fn parse(&self, input: &str) -> Result<Vec<PlaneSeat>, AoCError> {
+        parse::lines(input)
+            .map(|(i, boarding_pass)| PlaneSeat::new(boarding_pass.trim(), i))
+            .collect()
+    }
 
-            ids.sort();
-            let mut prev_id = i32::MAX;
-            for id in ids {
-                if id - prev_id > 1 {
-                    println!("The missing seat ID is {}", id-1);
-                    break;
-                }
-                prev_id = id;
+    fn part1(&self, seats: &Vec<PlaneSeat>) -> Result<Answer, AoCError> {
+        seats.iter().map(|seat| seat.id).max()
+            .map(Answer::from)
+            .ok_or_else(|| AoCError::no_solution("No boarding passes"))
+    }
+
+    fn part2(&self, seats: &Vec<PlaneSeat>) -> Result<Answer, AoCError> {
+        let mut ids: Vec<i32> = seats.iter().map(|seat| seat.id).collect();
+        ids.sort_unstable();
+        for (prev_id, id) in ids.iter().zip(ids.iter().skip(1)) {
+            if id - prev_id > 1 {
+                return Ok((id - 1).into());
             }
-        },
-        Err(e) => {
-            println!("Could not read file \"{}\": {}", puzzle_input, e);
         }
+        Err(AoCError::no_solution("Did not find a missing seat ID"))
     }
-    println!("This is day 5.");
 }
 
 #[derive(Debug)]
-struct PlaneSeat {
+pub struct PlaneSeat {
+    #[allow(dead_code)] // only shown in debug output
     row: i32,
+    #[allow(dead_code)] // only shown in debug output
     col: i32,
     id: i32
 }
 
 impl PlaneSeat {
-    fn new(seat_spec: &str) -> PlaneSeat {
+    /// Decodes a boarding pass found in the given line of the input.
+    fn new(seat_spec: &str, line: usize) -> Result<PlaneSeat, AoCError> {
+        /// Returns the index of the first invalid character on error.
         fn bin_space_partitioning(
             code: &str,
             lower: char,
             upper: char,
             last: i32
-        ) -> i32 {
+        ) -> Result<i32, usize> {
             let mut low = 0;
             let mut high = last;
-            for c in code.chars() {
+            for (i, c) in code.chars().enumerate() {
                 if c == lower { high = (high + low) / 2; }
                 else if c == upper { low = (high + low) / 2 + 1; }
-                else {
-                    panic!(
-                        "Invalid code for lower={}, upper={}: {}",
-                        lower, upper, code
-                    );
-                }
+                else { return Err(i); }
             }
-            assert_eq!(low, high);
-            low
+            Ok(low)
         }
 
-        assert_eq!(seat_spec.chars().count(), 10);
-        let row = bin_space_partitioning(&seat_spec[0..7], 'F', 'B', 127);
-        let col = bin_space_partitioning(&seat_spec[7..10], 'L', 'R', 7);
-        PlaneSeat{
+        if seat_spec.len() != 10 || !seat_spec.is_ascii() {
+            return Err(AoCError::parse(line, 1, format!(
+                "Boarding pass \"{}\" does not have 10 characters", seat_spec
+            )));
+        }
+        let row = bin_space_partitioning(&seat_spec[0..7], 'F', 'B', 127)
+            .map_err(|i| AoCError::parse(
+                line, i + 1, "Expected 'F' or 'B' in the row code"
+            ))?;
+        let col = bin_space_partitioning(&seat_spec[7..10], 'L', 'R', 7)
+            .map_err(|i| AoCError::parse(
+                line, i + 8, "Expected 'L' or 'R' in the column code"
+            ))?;
+        Ok(PlaneSeat{
             row,
             col,
             id: row * 8 + col,
-        }
+        })
     }
 }
@@ -1,58 +1,55 @@
 use std::collections::HashSet;
 use std::vec::Vec;
-use crate::fileutils::{self};
+use aoc_core::{AoCError, Answer, Solution, parse};
 
-pub fn run_day06(puzzle_input: &str) {
-    match fileutils::read_lines(puzzle_input) {
-        Ok(lines) => {
-            let mut group_answers_any: HashSet<char> = HashSet::new();
-            let mut group_answers_every: Vec<HashSet<char>> = Vec::new();
-            let mut answers_sum_any = 0;
-            let mut first_person_in_group = true;
-            group_answers_every.push(HashSet::new());
+pub struct Day06;
 
-            for line in lines {
-                if let Ok(line) = line {
-                    if line.trim().is_empty() {
-                        // start new passenger group
-                        group_answers_any.clear();
-                        group_answers_every.push(HashSet::new());
-                        first_person_in_group = true;
-                        continue;
-                    }
-                    if first_person_in_group {
-                        for c in line.chars() {
-                            group_answers_every.last_mut().unwrap().insert(c);
-                        }
-                        first_person_in_group = false;
-                    } else {
-                        let mut person_answers = HashSet::new();
-                        for c in line.chars() {
-                            person_answers.insert(c);
-                        }
-                        *group_answers_every.last_mut().unwrap() =
-                            group_answers_every
-                            .last()
-                            .unwrap()
-                            .intersection(&person_answers)
-                            .cloned().collect();
-                    }
-                    for c in line.chars() {
-                        answers_sum_any +=
-                            (!group_answers_any.contains(&c)) as i32;
-                        group_answers_any.insert(c);
-                    }
+impl Solution for Day06 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 6;
 
-                }
+    /// One entry per passenger group, holding each person's answers.
+    type Input = Vec<Vec<HashSet<char>>>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Vec<HashSet<char>>>, AoCError> {
+        for (i, line) in parse::lines(input) {
+            if let Some(col) = line.trim().chars().position(|c| !c.is_ascii_lowercase()) {
+                return Err(AoCError::parse(i, col + 1, "Expected a question from a to z"));
             }
-            println!("Sum of group 'any' answers: {}", answers_sum_any);
-            let answers_sum_every: u32 = group_answers_every.iter()
-                .map(|ans_set| ans_set.len() as u32)
-                .sum();
-            println!("Sum of group 'every' answers: {}", answers_sum_every);
-        },
-        Err(e) => {
-            println!("Could not read file \"{}\": {}", puzzle_input, e);
         }
+        Ok(parse::blocks(input)
+            .map(|group| group.lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.trim().chars().collect())
+                .collect::<Vec<HashSet<char>>>())
+            .collect())
+    }
+
+    fn part1(&self, groups: &Vec<Vec<HashSet<char>>>) -> Result<Answer, AoCError> {
+        let answers_sum_any: usize = groups.iter()
+            .map(|group| {
+                let mut group_answers_any: HashSet<char> = HashSet::new();
+                for person_answers in group {
+                    group_answers_any.extend(person_answers);
+                }
+                group_answers_any.len()
+            })
+            .sum();
+        Ok(answers_sum_any.into())
+    }
+
+    fn part2(&self, groups: &Vec<Vec<HashSet<char>>>) -> Result<Answer, AoCError> {
+        let answers_sum_every: usize = groups.iter()
+            .map(|group| {
+                let mut group_answers_every: HashSet<char> = group[0].clone();
+                for person_answers in &group[1..] {
+                    group_answers_every = group_answers_every
+                        .intersection(person_answers)
+                        .cloned().collect();
+                }
+                group_answers_every.len()
+            })
+            .sum();
+        Ok(answers_sum_every.into())
     }
 }
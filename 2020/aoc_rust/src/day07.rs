@@ -1,43 +1,188 @@
 extern crate regex;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
 use std::string::String;
 use regex::Regex;
+use serde::Serialize;
+use aoc_core::{AoCError, Answer, Solution, parse};
 
-pub fn run_day07(puzzle_input: &str) {
-    let contents = std::fs::read_to_string(&puzzle_input)
-        .expect("Something went wrong reading the file");
-
-    let rs = RuleSet::new(&contents);
-    let valid_outermost_bags = rs.get_valid_outermost_bags("shiny gold");
-    println!(
-        "{:?}\n->number of valid outermost bags: {}",
-        valid_outermost_bags,
-        valid_outermost_bags.len(),
-    );
-
-    println!(
-        "Number of bags in my shiny gold bag: {}",
-        rs.get_number_of_nested_bags("shiny gold", None)
-    )
+pub struct Day07;
+
+impl Solution for Day07 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 7;
+
+    type Input = RuleSet;
+
+    fn parse(&self, input: &str) -> Result<RuleSet, AoCError> {
+        let rs = RuleSet::new(input)?;
+        rs.validate().map_err(|problems| {
+            let problems: Vec<String> = problems.iter().map(|problem| problem.to_string()).collect();
+            AoCError::malformed(problems.join("; "))
+        })?;
+        Ok(rs)
+    }
+
+    fn part1(&self, rs: &RuleSet) -> Result<Answer, AoCError> {
+        Ok(rs.get_valid_outermost_bags("shiny gold").len().into())
+    }
+
+    fn part2(&self, rs: &RuleSet) -> Result<Answer, AoCError> {
+        Ok(rs.get_number_of_nested_bags("shiny gold")?.into())
+    }
+}
+
+/// A bag colour, interned as its index in `RuleSet::colors`.
+pub type ColorId = usize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BagContent {
+    pub num: u64,
+    pub color: ColorId,
 }
 
+/// The bag rules as a directed graph from every colour to the colours its
+/// bags contain, weighted by how many bags of each they contain.
 #[derive(Debug)]
-struct BagContent {
-    num: u32,
-    color: String
+pub struct RuleSet {
+    colors: Vec<String>,
+    ids: HashMap<String, ColorId>,
+    /// The contents of the bags of every colour, in the order of the rule.
+    contents: Vec<Vec<BagContent>>,
+    /// The colours whose bags directly contain bags of every colour.
+    parents: Vec<Vec<ColorId>>,
+    /// Whether every colour has a rule of its own, rather than only being
+    /// mentioned in the contents of other bags.
+    has_rule: Vec<bool>,
+}
+
+/// Something that makes the rules contradictory or incomplete.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuleProblem {
+    /// Bags that contain themselves, possibly through other bags. The
+    /// component holds all colours whose bags contain each other, sorted
+    /// by their first appearance in the rules, and the chain is the
+    /// shortest way from the first of them back to itself, e.g.
+    /// `[a, b, a]` if `a` bags contain `b` bags and vice versa.
+    Cycle { component: Vec<String>, chain: Vec<String> },
+    /// Bags of a colour without a rule of their own, along with the
+    /// colours whose rules mention them.
+    Dangling { color: String, containers: Vec<String> },
+}
+
+impl fmt::Display for RuleProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let quoted = |colors: &[String]| -> Vec<String> {
+            colors.iter().map(|color| format!("\"{}\"", color)).collect()
+        };
+        match self {
+            RuleProblem::Cycle { component, chain } => {
+                write!(f, "Bags contain themselves: {}", quoted(chain).join(" -> "))?;
+                // The first colour of the chain appears twice.
+                match component.len() + 1 - chain.len() {
+                    0 => Ok(()),
+                    1 => write!(f, " (and so does 1 other colour)"),
+                    others => write!(f, " (and so do {} other colours)", others),
+                }
+            },
+            RuleProblem::Dangling { color, containers } => write!(
+                f, "There is no rule for \"{}\" bags, which are in {} bags",
+                color, quoted(containers).join(", ")
+            ),
+        }
+    }
+}
+
+/// Which way to follow the containment edges from a colour.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// To the bags inside bags of the colour.
+    Contents,
+    /// To the bags that contain bags of the colour.
+    Containers,
+    Both,
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Direction, String> {
+        match s {
+            "contents" => Ok(Direction::Contents),
+            "containers" => Ok(Direction::Containers),
+            "both" => Ok(Direction::Both),
+            _ => Err(format!(
+                "Unknown direction \"{}\", expected contents, containers or both", s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            Direction::Contents => "contents",
+            Direction::Containers => "containers",
+            Direction::Both => "both",
+        })
+    }
+}
+
+/// The part of the graph around a colour to highlight in an export.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Highlight {
+    pub color: ColorId,
+    pub direction: Direction,
+    /// Whether to leave out everything that isn't highlighted.
+    pub only: bool,
+}
+
+/// A chain of bags from an outer bag to an inner bag, where each bag
+/// directly contains the next.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BagPath {
+    pub colors: Vec<ColorId>,
+    /// How many of the innermost bags one outermost bag contains along
+    /// this path, i.e. the product of the numbers of bags on the way.
+    pub multiplicity: u64,
+}
+
+/// The share of one kind of contents in the number of nested bags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChildCount {
+    pub content: BagContent,
+    /// The number of bags inside one bag of the content's colour.
+    pub nested: u64,
+    /// The bags of the content's colour along with everything inside them.
+    pub total: u64,
 }
 
-struct RuleSet {
-    rules: HashMap<String, HashMap<String, BagContent>>,
+/// What `RuleSet::depth_first` has come across, given the colours on the
+/// path from the start to the current colour.
+enum Walk<'a> {
+    /// The content with the given index of the current colour.
+    Content(&'a [ColorId], usize),
+    /// All contents of the current colour have been looked at, and the
+    /// walk is about to go back to the colour before it.
+    Leave(&'a [ColorId]),
+}
+
+/// How far the counting of nested bags got for a colour.
+#[derive(Clone, Copy)]
+enum Count {
+    Unvisited,
+    InProgress,
+    Done(u64),
 }
 
 impl RuleSet {
-    fn new(rules_text: &str) -> RuleSet {
+    fn new(rules_text: &str) -> Result<RuleSet, AoCError> {
         lazy_static! {
-            // (?xm) enables "insignificant whitespace mode" and
-            // "multiline mode":
-            static ref RULE: Regex = Regex::new(r"(?xm)
+            // (?x) enables "insignificant whitespace mode":
+            static ref RULE: Regex = Regex::new(r"(?x)
                 ^(?P<container_color>\w+\s\w+)\s  # container color
                 bags\scontain\s
                 (?P<contents_text>.*)$
@@ -50,89 +195,777 @@ impl RuleSet {
         }
 
         let mut rs = RuleSet {
-            rules: HashMap::new()
+            colors: Vec::new(),
+            ids: HashMap::new(),
+            contents: Vec::new(),
+            parents: Vec::new(),
+            has_rule: Vec::new(),
+        };
+        for (i, line) in parse::lines(rules_text) {
+            let rule_cap = RULE.captures(line).ok_or_else(|| AoCError::parse(
+                i, 1, "Expected \"<color> bags contain <contents>\""
+            ))?;
+            let container = rs.intern(rule_cap.name("container_color").unwrap().as_str());
+            if rs.has_rule[container] {
+                return Err(AoCError::parse(i, 1, format!(
+                    "There already is a rule for \"{}\" bags", rs.colors[container]
+                )));
+            }
+            rs.has_rule[container] = true;
+            let contents = rule_cap.name("contents_text").unwrap();
+            if contents.as_str() == "no other bags." { continue; }
+
+            // The contents must be a list of matches without any gaps:
+            let mut end = contents.start();
+            for content_cap in CONTENT.captures_iter(contents.as_str()) {
+                let whole = content_cap.get(0).unwrap();
+                if contents.start() + whole.start() != end { break; }
+                end = contents.start() + whole.end();
+                let color = rs.intern(content_cap.name("content_color").unwrap().as_str());
+                let num = content_cap.name("num").unwrap();
+                let num = parse::value::<u64>(num.as_str(), i, contents.start() + num.start() + 1)?;
+                rs.contents[container].push(BagContent { num, color });
+                if !rs.parents[color].contains(&container) {
+                    rs.parents[color].push(container);
+                }
+            }
+            if rs.contents[container].is_empty() || end != line.len() {
+                return Err(AoCError::parse(
+                    i, end + 1, "Expected \"<n> <color> bag(s)\""
+                ));
+            }
+        }
+        Ok(rs)
+    }
+
+    fn intern(&mut self, color: &str) -> ColorId {
+        if let Some(&id) = self.ids.get(color) {
+            return id;
+        }
+        let id = self.colors.len();
+        self.colors.push(color.to_string());
+        self.ids.insert(color.to_string(), id);
+        self.contents.push(Vec::new());
+        self.parents.push(Vec::new());
+        self.has_rule.push(false);
+        id
+    }
+
+    /// The number of colours, which are numbered from 0.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    pub fn id(&self, color: &str) -> Option<ColorId> {
+        self.ids.get(color).copied()
+    }
+
+    pub fn color(&self, id: ColorId) -> &str {
+        &self.colors[id]
+    }
+
+    pub fn contents(&self, id: ColorId) -> &[BagContent] {
+        &self.contents[id]
+    }
+
+    pub fn parents(&self, id: ColorId) -> &[ColorId] {
+        &self.parents[id]
+    }
+
+    /// The colours of all bags that eventually contain a bag of the given
+    /// colour, which itself is only included if it contains itself.
+    pub fn get_valid_outermost_bags(&self, inner_color: &str) -> Vec<ColorId> {
+        match self.id(inner_color) {
+            Some(inner) => self.containers(inner),
+            None => Vec::new(),
+        }
+    }
+
+    /// The colours of all bags that eventually contain a bag of the given
+    /// colour.
+    pub fn containers(&self, inner: ColorId) -> Vec<ColorId> {
+        self.closure(inner, |color| self.parents[color].iter().copied())
+    }
+
+    /// The colours of all bags that are eventually inside a bag of the
+    /// given colour.
+    pub fn all_contents(&self, outer: ColorId) -> Vec<ColorId> {
+        self.closure(outer, |color| self.contents[color].iter().map(|content| content.color))
+    }
+
+    /// All colours that can be reached from `start` by repeatedly going to
+    /// the `next` colours, without `start` unless it is on a cycle.
+    fn closure<F, I>(&self, start: ColorId, next: F) -> Vec<ColorId>
+    where F: Fn(ColorId) -> I, I: Iterator<Item = ColorId> {
+        let mut seen = vec![false; self.len()];
+        let mut reached: Vec<ColorId> = Vec::new();
+        let mut todo: Vec<ColorId> = vec![start];
+        while let Some(color) = todo.pop() {
+            for other in next(color) {
+                if !seen[other] {
+                    seen[other] = true;
+                    reached.push(other);
+                    todo.push(other);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Which colours and containment edges belong to the highlighted
+    /// sub-graph: all colours with bags inside `highlight.color` bags
+    /// and/or all colours with bags that contain them, depending on the
+    /// direction, and the edges between them on the way from or to the
+    /// chosen colour.
+    fn highlighted(&self, highlight: &Highlight) -> (Vec<bool>, impl Fn(ColorId, ColorId) -> bool) {
+        let mark = |colors: Vec<ColorId>| {
+            let mut marked = vec![false; self.len()];
+            marked[highlight.color] = true;
+            for color in colors {
+                marked[color] = true;
+            }
+            marked
+        };
+        let inside = match highlight.direction {
+            Direction::Containers => vec![false; self.len()],
+            _ => mark(self.all_contents(highlight.color)),
+        };
+        let outside = match highlight.direction {
+            Direction::Contents => vec![false; self.len()],
+            _ => mark(self.containers(highlight.color)),
+        };
+        let nodes: Vec<bool> = inside.iter().zip(&outside).map(|(&a, &b)| a || b).collect();
+        (nodes, move |outer: ColorId, inner: ColorId| inside[outer] || outside[inner])
+    }
+
+    /// Writes the graph in the DOT language of Graphviz, with the edges
+    /// labelled with the number of bags.
+    pub fn write_dot<W: Write>(&self, out: &mut W, highlight: Option<&Highlight>) -> io::Result<()> {
+        let (nodes, edges) = match highlight {
+            Some(highlight) => {
+                let (nodes, edges) = self.highlighted(highlight);
+                (Some(nodes), Some(edges))
+            },
+            None => (None, None),
         };
-        for rule_cap in RULE.captures_iter(&rules_text) {
-            // println!("Capture: {:?}", rule_cap);
-            let mut bag_contents: HashMap<String, BagContent> = HashMap::new();
-            for content_cap in CONTENT.captures_iter(rule_cap
-                .name("contents_text").unwrap().as_str()
-            ) {
-                let color = content_cap.name("content_color")
-                    .unwrap().as_str();
-                bag_contents.insert(
-                    color.to_string(),
-                    BagContent {
-                        num: content_cap.name("num")
-                            .unwrap().as_str().parse::<u32>().unwrap(),
-                        color: color.to_string()
+        let only = highlight.is_some_and(|highlight| highlight.only);
+        let quoted = |color: ColorId| format!("\"{}\"", self.colors[color].replace('"', "\\\""));
+
+        writeln!(out, "digraph bags {{")?;
+        writeln!(out, "    node [shape=box];")?;
+        for color in 0..self.len() {
+            let marked = nodes.as_ref().is_some_and(|nodes| nodes[color]);
+            if only && !marked { continue; }
+            match highlight {
+                Some(highlight) if highlight.color == color => writeln!(
+                    out, "    {} [style=filled, fillcolor=gold];", quoted(color)
+                )?,
+                _ if marked => writeln!(
+                    out, "    {} [style=filled, fillcolor=lightblue];", quoted(color)
+                )?,
+                _ => writeln!(out, "    {};", quoted(color))?,
+            }
+        }
+        for outer in 0..self.len() {
+            for content in &self.contents[outer] {
+                let marked = edges.as_ref().is_some_and(|edges| edges(outer, content.color));
+                if only && !marked { continue; }
+                let style = if marked { ", color=red, penwidth=2" } else { "" };
+                writeln!(
+                    out, "    {} -> {} [label={}{}];",
+                    quoted(outer), quoted(content.color), content.num, style
+                )?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+
+    /// Writes the graph as JSON, with a list of the colours and a list of
+    /// the containment edges.
+    pub fn write_json<W: Write>(&self, out: &mut W, highlight: Option<&Highlight>) -> io::Result<()> {
+        #[derive(Serialize)]
+        struct Graph<'a> {
+            highlight: Option<JsonHighlight<'a>>,
+            nodes: Vec<Node<'a>>,
+            edges: Vec<Edge<'a>>,
+        }
+        #[derive(Serialize)]
+        struct JsonHighlight<'a> {
+            color: &'a str,
+            direction: String,
+        }
+        #[derive(Serialize)]
+        struct Node<'a> {
+            color: &'a str,
+            highlighted: bool,
+        }
+        #[derive(Serialize)]
+        struct Edge<'a> {
+            outer: &'a str,
+            inner: &'a str,
+            num: u64,
+            highlighted: bool,
+        }
+
+        let marks = highlight.map(|highlight| self.highlighted(highlight));
+        let only = highlight.is_some_and(|highlight| highlight.only);
+        let graph = Graph {
+            highlight: highlight.map(|highlight| JsonHighlight {
+                color: &self.colors[highlight.color],
+                direction: highlight.direction.to_string(),
+            }),
+            nodes: (0..self.len())
+                .map(|color| Node {
+                    color: &self.colors[color],
+                    highlighted: marks.as_ref().is_some_and(|(nodes, _)| nodes[color]),
+                })
+                .filter(|node| !only || node.highlighted)
+                .collect(),
+            edges: (0..self.len())
+                .flat_map(|outer| self.contents[outer].iter().map(move |content| (outer, content)))
+                .map(|(outer, content)| Edge {
+                    outer: &self.colors[outer],
+                    inner: &self.colors[content.color],
+                    num: content.num,
+                    highlighted: marks.as_ref().is_some_and(|(_, edges)| edges(outer, content.color)),
+                })
+                .filter(|edge| !only || edge.highlighted)
+                .collect(),
+        };
+        serde_json::to_writer_pretty(&mut *out, &graph).map_err(io::Error::other)?;
+        writeln!(out)
+    }
+
+    /// Checks that no bag contains itself and that every colour has a rule.
+    /// Returns all problems at once, cycles first.
+    pub fn validate(&self) -> Result<(), Vec<RuleProblem>> {
+        let names = |ids: &[ColorId]| -> Vec<String> {
+            ids.iter().map(|&id| self.colors[id].clone()).collect()
+        };
+        let mut problems: Vec<RuleProblem> = Vec::new();
+        let mut cycles: Vec<Vec<ColorId>> = self.components().into_iter()
+            .filter(|component| {
+                component.len() > 1 || self.contents[component[0]].iter()
+                    .any(|content| content.color == component[0])
+            })
+            .collect();
+        for component in &mut cycles {
+            component.sort_unstable();
+        }
+        cycles.sort_unstable();
+        for component in cycles {
+            let chain = self.shortest_cycle(&component);
+            problems.push(RuleProblem::Cycle { component: names(&component), chain: names(&chain) });
+        }
+        for color in (0..self.len()).filter(|&color| !self.has_rule[color]) {
+            problems.push(RuleProblem::Dangling {
+                color: self.colors[color].clone(),
+                containers: names(&self.parents[color]),
+            });
+        }
+        if problems.is_empty() { Ok(()) } else { Err(problems) }
+    }
+
+    /// The strongly connected components of the graph, i.e. the largest
+    /// groups of colours whose bags all (indirectly) contain each other,
+    /// found with an iterative version of Tarjan's algorithm.
+    fn components(&self) -> Vec<Vec<ColorId>> {
+        const UNVISITED: usize = usize::MAX;
+        let mut index = vec![UNVISITED; self.len()];
+        let mut low_link = vec![UNVISITED; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack: Vec<ColorId> = Vec::new();
+        let mut next_index = 0;
+        let mut components: Vec<Vec<ColorId>> = Vec::new();
+        for root in 0..self.len() {
+            if index[root] != UNVISITED { continue; }
+            let walked = self.depth_first(root, |walk| {
+                let (Walk::Content(path, _) | Walk::Leave(path)) = walk;
+                let color = *path.last().unwrap();
+                if index[color] == UNVISITED {
+                    index[color] = next_index;
+                    low_link[color] = next_index;
+                    next_index += 1;
+                    stack.push(color);
+                    on_stack[color] = true;
+                }
+                match walk {
+                    Walk::Content(_, i) => {
+                        let content = self.contents[color][i].color;
+                        if on_stack[content] {
+                            low_link[color] = low_link[color].min(index[content]);
+                        }
+                        Ok(index[content] == UNVISITED)
+                    },
+                    Walk::Leave(path) => {
+                        if let Some(&parent) = path.iter().nth_back(1) {
+                            low_link[parent] = low_link[parent].min(low_link[color]);
+                        }
+                        if low_link[color] == index[color] {
+                            let start = stack.iter().rposition(|&c| c == color).unwrap();
+                            let component: Vec<ColorId> = stack.drain(start..).collect();
+                            for &member in &component {
+                                on_stack[member] = false;
+                            }
+                            components.push(component);
+                        }
+                        Ok(false)
+                    },
+                }
+            });
+            walked.unwrap_or_else(|never: std::convert::Infallible| match never {});
+        }
+        components
+    }
+
+    /// The shortest chain of containment from the first colour of a
+    /// cyclic component back to itself.
+    fn shortest_cycle(&self, component: &[ColorId]) -> Vec<ColorId> {
+        let start = component[0];
+        let mut previous: HashMap<ColorId, ColorId> = HashMap::new();
+        let mut todo: VecDeque<ColorId> = VecDeque::from([start]);
+        while let Some(color) = todo.pop_front() {
+            for content in &self.contents[color] {
+                if content.color == start {
+                    let mut chain = vec![start, color];
+                    while let Some(&before) = previous.get(chain.last().unwrap()) {
+                        chain.push(before);
                     }
-                );
-                // println!("\tContent: {:?}",
-                //     bag_contents.get(color.to_string()));
+                    chain.reverse();
+                    return chain;
+                }
+                if component.binary_search(&content.color).is_ok()
+                    && !previous.contains_key(&content.color) {
+                    previous.insert(content.color, color);
+                    todo.push_back(content.color);
+                }
             }
+        }
+        unreachable!("The component is not cyclic")
+    }
 
-            rs.rules.insert(
-                rule_cap.name("container_color")
-                    .unwrap().as_str().to_string(),
-                bag_contents
-            );
-        }
-        rs
-    }
-
-    fn get_valid_outermost_bags(&self, inner_color: &str) -> HashSet<String> {
-        fn insert_recursively(
-            rs: &RuleSet,
-            valid: &mut HashSet<String>,
-            new_color: &str
-        ) {
-            if valid.contains(new_color) { return; }
-            for (outer, contents) in rs.rules.iter() {
-                if contents.contains_key(new_color) {
-                    insert_recursively(rs, valid, outer);
+    /// Walks the graph depth-first from `start`, iteratively so that long
+    /// chains of bags don't overflow the stack. `visit` is told about every
+    /// content of the colours along the way and returns whether to walk
+    /// into it; its return value is ignored when leaving a colour. The walk
+    /// stops at the first error of `visit`.
+    fn depth_first<E, F>(&self, start: ColorId, mut visit: F) -> Result<(), E>
+    where F: FnMut(Walk) -> Result<bool, E> {
+        let mut path: Vec<ColorId> = vec![start];
+        // The number of contents of every colour on the path that have
+        // been looked at already.
+        let mut looked_at: Vec<usize> = vec![0];
+        while let Some(&color) = path.last() {
+            let i = *looked_at.last().unwrap();
+            if i < self.contents[color].len() {
+                *looked_at.last_mut().unwrap() += 1;
+                if visit(Walk::Content(&path, i))? {
+                    path.push(self.contents[color][i].color);
+                    looked_at.push(0);
                 }
+            } else {
+                visit(Walk::Leave(&path))?;
+                path.pop();
+                looked_at.pop();
             }
-            valid.insert(new_color.to_string());
         }
+        Ok(())
+    }
+
+    /// How many bags of colour `inner` a bag of colour `outer` directly
+    /// contains.
+    fn num_inside(&self, outer: ColorId, inner: ColorId) -> u64 {
+        self.contents[outer].iter()
+            .filter(|content| content.color == inner)
+            .map(|content| content.num)
+            .sum()
+    }
 
-        let mut valid: HashSet<String> = HashSet::new();
-        insert_recursively(self, &mut valid, &inner_color);
+    /// The colours ordered so that every bag comes before its contents,
+    /// with Kahn's algorithm. Colours on cycles are left out.
+    fn topological_order(&self) -> Vec<ColorId> {
+        let mut containers: Vec<usize> = self.parents.iter().map(|parents| parents.len()).collect();
+        let mut order: Vec<ColorId> = (0..self.len()).filter(|&color| containers[color] == 0).collect();
+        let mut i = 0;
+        while let Some(&color) = order.get(i) {
+            i += 1;
+            let mut inner: Vec<ColorId> = self.contents[color].iter().map(|content| content.color).collect();
+            inner.sort_unstable();
+            inner.dedup();
+            for inner in inner {
+                containers[inner] -= 1;
+                if containers[inner] == 0 {
+                    order.push(inner);
+                }
+            }
+        }
+        order
+    }
 
-        // Remove inner color again as we only want the outermost bag colors:
-        valid.remove(&inner_color.to_string());
-        valid
+    /// The number of paths from `from` to `to`, or from all outermost bags
+    /// that contain `to` bags if `from` is `None`, saturating at
+    /// `u64::MAX`. Like in `paths`, a single `to` bag is a path from `to`.
+    pub fn count_paths(&self, from: Option<ColorId>, to: ColorId) -> u64 {
+        // The number of paths from every colour to `to`, filled in from the
+        // inside out.
+        let mut paths = vec![0u64; self.len()];
+        paths[to] = 1;
+        for &color in self.topological_order().iter().rev() {
+            if color == to { continue; }
+            let mut inner: Vec<ColorId> = self.contents[color].iter().map(|content| content.color).collect();
+            inner.sort_unstable();
+            inner.dedup();
+            paths[color] = inner.iter().fold(0, |sum: u64, &inner| sum.saturating_add(paths[inner]));
+        }
+        match from {
+            Some(from) => paths[from],
+            None => self.outermost(to).iter().fold(0, |sum, &color| sum.saturating_add(paths[color])),
+        }
     }
 
-    fn get_number_of_nested_bags(
+    /// The colours of the bags that contain bags of colour `inner`, but
+    /// aren't inside any other bag themselves.
+    fn outermost(&self, inner: ColorId) -> Vec<ColorId> {
+        let mut outermost: Vec<ColorId> = self.containers(inner).into_iter()
+            .filter(|&color| self.parents[color].is_empty())
+            .collect();
+        outermost.sort_unstable();
+        outermost
+    }
+
+    /// Up to `limit` of the paths from `from` to `to`, or from all
+    /// outermost bags that contain `to` bags if `from` is `None`. Fails if
+    /// a multiplicity doesn't fit into a `u64`. The only path from `to`
+    /// itself is the single `to` bag.
+    pub fn paths(
         &self,
-        outer_color: &str,
-        visited_colors: Option<HashSet<String>>
-    ) -> u32 {
-        let mut count: u32 = 0;
-        let mut new_visited_colors: HashSet<String>;
-        if let Some(visited_colors) = visited_colors {
-            if visited_colors.contains(outer_color) {
-                panic!(
-                    "Loop detected: outer_color=\"{}\"\n{:?}",
-                    outer_color,
-                    visited_colors
-                );
+        from: Option<ColorId>,
+        to: ColorId,
+        limit: usize
+    ) -> Result<Vec<BagPath>, AoCError> {
+        let mut leads_to = vec![false; self.len()];
+        leads_to[to] = true;
+        for color in self.containers(to) {
+            leads_to[color] = true;
+        }
+        let starts = match from {
+            Some(from) => vec![from],
+            None => self.outermost(to),
+        };
+
+        let mut paths: Vec<BagPath> = Vec::new();
+        if limit == 0 { return Ok(paths); }
+        for start in starts {
+            if !leads_to[start] { continue; }
+            if start == to {
+                paths.push(BagPath { colors: vec![to], multiplicity: 1 });
+                continue;
+            }
+            // The multiplicity of every colour on the path. The walk stops
+            // with `None` once there are enough paths.
+            let mut multiplicities: Vec<u64> = vec![1];
+            let walked = self.depth_first(start, |walk| match walk {
+                Walk::Content(path, i) => {
+                    let color = *path.last().unwrap();
+                    let content = self.contents[color][i];
+                    let seen_before = self.contents[color][..i].iter()
+                        .any(|other| other.color == content.color);
+                    if !leads_to[content.color] || seen_before { return Ok(false); }
+                    let multiplicity = multiplicities.last().unwrap()
+                        .checked_mul(self.num_inside(color, content.color))
+                        .ok_or_else(|| Some(AoCError::no_solution(format!(
+                            "The number of \"{}\" bags on a path doesn't fit into 64 bits",
+                            self.colors[to]
+                        ))))?;
+                    if content.color != to {
+                        multiplicities.push(multiplicity);
+                        return Ok(true);
+                    }
+                    paths.push(BagPath {
+                        colors: path.iter().copied().chain([to]).collect(),
+                        multiplicity,
+                    });
+                    if paths.len() == limit { Err(None) } else { Ok(false) }
+                },
+                Walk::Leave(_) => {
+                    multiplicities.pop();
+                    Ok(false)
+                },
+            });
+            match walked {
+                Ok(()) => (),
+                Err(None) => break,
+                Err(Some(e)) => return Err(e),
             }
-            new_visited_colors = visited_colors.clone();
-        } else {
-            new_visited_colors = HashSet::new();
-        }
-        new_visited_colors.insert(outer_color.to_string());
-        if let Some(content) = self.rules.get(outer_color) {
-            for (color, content) in content.iter() {
-                count += content.num;
-                count += content.num * self.get_number_of_nested_bags(
-                    color,
-                    Some(new_visited_colors.clone())
-                )
+        }
+        Ok(paths)
+    }
+
+    /// How many bags each kind of contents of an `outer` bag accounts for.
+    /// The totals add up to the number of nested bags.
+    pub fn breakdown(&self, outer: ColorId) -> Result<Vec<ChildCount>, AoCError> {
+        let counts = self.nested_counts(outer)?;
+        Ok(self.contents[outer].iter()
+            .map(|&content| {
+                let nested = counts[content.color].unwrap();
+                // Fits, since the count of `outer` does.
+                ChildCount { content, nested, total: content.num * (nested + 1) }
+            })
+            .collect())
+    }
+
+    /// The longest chain of bags inside each other, starting from `outer`
+    /// or else from any colour. Of several equally long chains, this is
+    /// the first one in the order of the rules.
+    pub fn deepest_chain(&self, outer: Option<ColorId>) -> Vec<ColorId> {
+        // The length of the longest chain from every colour, and the colour
+        // it continues with, filled in from the inside out.
+        let mut depths = vec![0usize; self.len()];
+        let mut next: Vec<Option<ColorId>> = vec![None; self.len()];
+        for &color in self.topological_order().iter().rev() {
+            for content in &self.contents[color] {
+                if depths[content.color] + 1 > depths[color] || next[color].is_none() {
+                    depths[color] = depths[content.color] + 1;
+                    next[color] = Some(content.color);
+                }
             }
         }
-        count
+        let Some(start) = outer.or_else(|| {
+            (0..self.len()).max_by_key(|&color| (depths[color], std::cmp::Reverse(color)))
+        }) else { return Vec::new(); };
+        std::iter::successors(Some(start), |&color| next[color]).collect()
+    }
+
+    /// The number of bags inside a bag of the given colour.
+    pub fn get_number_of_nested_bags(&self, outer_color: &str) -> Result<u64, AoCError> {
+        match self.id(outer_color) {
+            Some(outer) => Ok(self.nested_counts(outer)?[outer].unwrap()),
+            None => Ok(0),
+        }
+    }
+
+    /// The number of bags inside a bag of every colour that is reachable
+    /// from `outer`, including `outer` itself. Every colour is counted
+    /// once, iteratively so that long chains of bags don't overflow the
+    /// stack. Fails if a bag contains itself or a count doesn't fit into
+    /// a `u64`.
+    fn nested_counts(&self, outer: ColorId) -> Result<Vec<Option<u64>>, AoCError> {
+        let mut counts = vec![Count::Unvisited; self.len()];
+        counts[outer] = Count::InProgress;
+        self.depth_first(outer, |walk| match walk {
+            Walk::Content(path, i) => {
+                let content = self.contents[*path.last().unwrap()][i];
+                match counts[content.color] {
+                    Count::Unvisited => {
+                        counts[content.color] = Count::InProgress;
+                        Ok(true)
+                    },
+                    Count::InProgress => Err(AoCError::no_solution(format!(
+                        "Bag \"{}\" contains itself", self.colors[content.color]
+                    ))),
+                    Count::Done(_) => Ok(false),
+                }
+            },
+            Walk::Leave(path) => {
+                let color = *path.last().unwrap();
+                let count = self.contents[color].iter()
+                    .try_fold(0u64, |count, content| {
+                        let Count::Done(nested) = counts[content.color] else { unreachable!() };
+                        nested.checked_add(1)?.checked_mul(content.num)?.checked_add(count)
+                    })
+                    .ok_or_else(|| AoCError::no_solution(format!(
+                        "The number of bags in a \"{}\" bag doesn't fit into 64 bits",
+                        self.colors[color]
+                    )))?;
+                counts[color] = Count::Done(count);
+                Ok(false)
+            },
+        })?;
+        Ok(counts.into_iter()
+            .map(|count| match count {
+                Count::Done(count) => Some(count),
+                _ => None,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A chain of `n` colours where every bag contains two of the next.
+    fn chain(n: usize) -> String {
+        let mut rules: Vec<String> = (1..n)
+            .map(|i| format!("color c{} bags contain 2 color c{} bags.", i - 1, i))
+            .collect();
+        rules.push(format!("color c{} bags contain no other bags.", n - 1));
+        rules.join("\n")
+    }
+
+    #[test]
+    fn test_graph() {
+        let rs = Day07.parse(include_str!("../../07/example1.txt")).unwrap();
+        let gold = rs.id("shiny gold").unwrap();
+        let mut parents: Vec<&str> = rs.parents(gold).iter().map(|&id| rs.color(id)).collect();
+        parents.sort_unstable();
+        assert_eq!(parents, vec!["bright white", "muted yellow"]);
+        let contents: Vec<(u64, &str)> = rs.contents(gold).iter()
+            .map(|content| (content.num, rs.color(content.color)))
+            .collect();
+        assert_eq!(contents, vec![(1, "dark olive"), (2, "vibrant plum")]);
+        assert!(rs.get_valid_outermost_bags("light red").is_empty());
+        assert!(rs.get_valid_outermost_bags("mauve pink").is_empty());
+        assert_eq!(
+            Day07.parse("light red bags contain no other bags.\nlight red bags contain no other bags.").err(),
+            Some(AoCError::parse(2, 1, "There already is a rule for \"light red\" bags"))
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let rs = RuleSet::new(
+            "a a bags contain 1 b b bag, 2 x x bags.\n\
+             b b bags contain 1 c c bag.\n\
+             c c bags contain 1 a a bag, 1 d d bag.\n\
+             d d bags contain 3 b b bags.\n\
+             e e bags contain 1 e e bag.\n\
+             f f bags contain 1 a a bag.\n"
+        ).unwrap();
+        let cycle = |component: &[&str], chain: &[&str]| RuleProblem::Cycle {
+            component: component.iter().map(|c| c.to_string()).collect(),
+            chain: chain.iter().map(|c| c.to_string()).collect(),
+        };
+        let problems = rs.validate().unwrap_err();
+        assert_eq!(problems, vec![
+            cycle(&["a a", "b b", "c c", "d d"], &["a a", "b b", "c c", "a a"]),
+            cycle(&["e e"], &["e e", "e e"]),
+            RuleProblem::Dangling { color: "x x".to_string(), containers: vec!["a a".to_string()] },
+        ]);
+        assert_eq!(
+            problems[0].to_string(),
+            "Bags contain themselves: \"a a\" -> \"b b\" -> \"c c\" -> \"a a\" \
+             (and so does 1 other colour)"
+        );
+        assert_eq!(
+            Day07.parse("a a bags contain 1 b b bag.\nc c bags contain 2 b b bags.").err(),
+            Some(AoCError::malformed(
+                "There is no rule for \"b b\" bags, which are in \"a a\", \"c c\" bags"
+            ))
+        );
+        let example = Day07.parse(include_str!("../../07/example2.txt")).unwrap();
+        assert_eq!(example.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_export() {
+        let rs = Day07.parse(
+            "a a bags contain 1 b b bag, 2 c c bags.\n\
+             b b bags contain 3 c c bags.\n\
+             c c bags contain no other bags.\n\
+             d d bags contain 4 a a bags.\n"
+        ).unwrap();
+        let mut dot: Vec<u8> = Vec::new();
+        rs.write_dot(&mut dot, None).unwrap();
+        assert_eq!(String::from_utf8(dot).unwrap(), "\
+            digraph bags {\n    node [shape=box];\n    \"a a\";\n    \"b b\";\n    \"c c\";\n    \"d d\";\n    \
+            \"a a\" -> \"b b\" [label=1];\n    \"a a\" -> \"c c\" [label=2];\n    \
+            \"b b\" -> \"c c\" [label=3];\n    \"d d\" -> \"a a\" [label=4];\n}\n"
+        );
+
+        // The edge from a a to c c bypasses b b, so it isn't on the way.
+        let highlight = Highlight { color: rs.id("b b").unwrap(), direction: Direction::Both, only: true };
+        let mut dot: Vec<u8> = Vec::new();
+        rs.write_dot(&mut dot, Some(&highlight)).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        assert!(dot.contains("    \"b b\" [style=filled, fillcolor=gold];\n"));
+        assert!(dot.contains("    \"d d\" -> \"a a\" [label=4, color=red, penwidth=2];\n"));
+        assert!(!dot.contains("\"a a\" -> \"c c\""));
+
+        let highlight = Highlight { direction: Direction::Contents, only: false, ..highlight };
+        let mut json: Vec<u8> = Vec::new();
+        rs.write_json(&mut json, Some(&highlight)).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["highlight"], serde_json::json!({"color": "b b", "direction": "contents"}));
+        let highlighted: Vec<&str> = json["nodes"].as_array().unwrap().iter()
+            .filter(|node| node["highlighted"] == true)
+            .map(|node| node["color"].as_str().unwrap())
+            .collect();
+        assert_eq!(highlighted, vec!["b b", "c c"]);
+        assert_eq!(
+            json["edges"][2],
+            serde_json::json!({"outer": "b b", "inner": "c c", "num": 3, "highlighted": true})
+        );
+        assert_eq!(json["edges"][3]["highlighted"], false);
+    }
+
+    #[test]
+    fn test_queries() {
+        let rs = Day07.parse(include_str!("../../07/example1.txt")).unwrap();
+        let id = |color: &str| rs.id(color).unwrap();
+        let names = |colors: &[ColorId]| -> Vec<&str> {
+            colors.iter().map(|&color| rs.color(color)).collect()
+        };
+        let gold = id("shiny gold");
+
+        let paths = rs.paths(None, gold, usize::MAX).unwrap();
+        let paths: Vec<(Vec<&str>, u64)> = paths.iter()
+            .map(|path| (names(&path.colors), path.multiplicity))
+            .collect();
+        assert_eq!(paths, vec![
+            (vec!["light red", "bright white", "shiny gold"], 1),
+            (vec!["light red", "muted yellow", "shiny gold"], 4),
+            (vec!["dark orange", "bright white", "shiny gold"], 3),
+            (vec!["dark orange", "muted yellow", "shiny gold"], 8),
+        ]);
+        assert_eq!(rs.count_paths(None, gold), 4);
+        assert_eq!(rs.count_paths(Some(id("muted yellow")), gold), 1);
+        assert_eq!(rs.paths(Some(id("light red")), gold, 1).unwrap().len(), 1);
+        assert_eq!(rs.count_paths(Some(id("faded blue")), gold), 0);
+        assert!(rs.paths(Some(id("faded blue")), gold, usize::MAX).unwrap().is_empty());
+        assert_eq!(rs.count_paths(None, id("faded blue")), 10);
+        assert_eq!(rs.paths(None, id("faded blue"), usize::MAX).unwrap().len(), 10);
+        assert_eq!(rs.count_paths(Some(gold), gold), 1);
+        assert_eq!(
+            rs.paths(Some(gold), gold, usize::MAX).unwrap(),
+            vec![BagPath { colors: vec![gold], multiplicity: 1 }]
+        );
+
+        let breakdown = rs.breakdown(gold).unwrap();
+        let breakdown: Vec<(&str, u64, u64)> = breakdown.iter()
+            .map(|child| (rs.color(child.content.color), child.nested, child.total))
+            .collect();
+        assert_eq!(breakdown, vec![("dark olive", 7, 8), ("vibrant plum", 11, 24)]);
+
+        assert_eq!(
+            names(&rs.deepest_chain(None)),
+            vec!["light red", "bright white", "shiny gold", "dark olive", "faded blue"]
+        );
+        assert_eq!(names(&rs.deepest_chain(Some(id("vibrant plum")))), vec!["vibrant plum", "faded blue"]);
+        assert_eq!(names(&rs.deepest_chain(Some(id("faded blue")))), vec!["faded blue"]);
+    }
+
+    #[test]
+    fn test_long_chain() {
+        let rs = Day07.parse(&chain(50_000)).unwrap();
+        assert_eq!(rs.get_valid_outermost_bags("color c49999").len(), 49_999);
+        assert_eq!(rs.get_number_of_nested_bags("color c49990"), Ok(1022));
+        assert_eq!(rs.deepest_chain(None).len(), 50_000);
+        assert_eq!(rs.count_paths(None, rs.id("color c49999").unwrap()), 1);
+        assert_eq!(
+            rs.get_number_of_nested_bags("color c0"),
+            Err(AoCError::no_solution(
+                "The number of bags in a \"color c49935\" bag doesn't fit into 64 bits"
+            ))
+        );
+        let cyclic = RuleSet::new("a b bags contain 1 c d bag.\nc d bags contain 2 a b bags.").unwrap();
+        assert_eq!(
+            cyclic.get_number_of_nested_bags("a b"),
+            Err(AoCError::no_solution("Bag \"a b\" contains itself"))
+        );
     }
 }
@@ -1,131 +1,488 @@
 extern crate regex;
 
+use std::fmt;
+use std::str::FromStr;
 use std::vec::Vec;
 use regex::Regex;
+use aoc_core::{AoCError, Answer, Solution, parse};
 
-pub fn run_day08(puzzle_input: &str) {
-    let mut program = Program::new(&puzzle_input);
-    if let ProgramResult::FoundLoop(pc, acc) = program.compute() {
-        println!("Acc before loop: {}, pc={}", acc, pc);
-    } else {
-        println!("No loop detected.");
+pub struct Day08;
+
+impl Solution for Day08 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 8;
+
+    type Input = Program;
+
+    fn parse(&self, input: &str) -> Result<Program, AoCError> {
+        Program::new(input)
+    }
+
+    fn part1(&self, program: &Program) -> Result<Answer, AoCError> {
+        let mut vm = Vm::new(program);
+        match vm.run() {
+            Stop::Halted(Halt::Repeat) => Ok(vm.acc().into()),
+            _ => Err(AoCError::no_solution("The program does not loop")),
+        }
     }
 
-    if let Some((_, acc, line)) = program.get_fixed_program() {
-        println!("Acc after fixing line {}: {}", line, acc);
-    } else {
-        println!("Could not fix program :(");
+    fn part2(&self, program: &Program) -> Result<Answer, AoCError> {
+        let (_, acc, _) = program.get_fixed_program().ok_or_else(|| {
+            AoCError::no_solution("No single nop/jmp flip makes the program terminate")
+        })?;
+        Ok(acc.into())
     }
 }
 
-#[derive(Clone)]
-struct Program {
-    instructions: Vec<Instruction>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Program {
+    instructions: Vec<Operation>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Nop(i32), // having an arg for NOP is important for code fixing
+    Acc(i32),
+    Jmp(i32)
+}
+
+/// The kind of an operation, without its argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpKind {
+    Nop,
+    Acc,
+    Jmp,
+}
+
+impl Operation {
+    pub fn kind(self) -> OpKind {
+        match self {
+            Operation::Nop(_) => OpKind::Nop,
+            Operation::Acc(_) => OpKind::Acc,
+            Operation::Jmp(_) => OpKind::Jmp,
+        }
+    }
+
+    pub fn arg(self) -> i32 {
+        match self {
+            Operation::Nop(arg) | Operation::Acc(arg) | Operation::Jmp(arg) => arg,
+        }
+    }
 }
 
-enum ProgramResult {
-    FoundLoop(usize, i32), // pc, acc
-    Terminated(i32), // acc
-    PcOutOfBounds(i32, i32) // pc, acc
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&format!("{} {:+}", self.kind(), self.arg()))
+    }
 }
 
-#[derive(Clone)]
-struct Instruction {
-    op: Operation,
-    call_count: u8
+impl FromStr for OpKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<OpKind, String> {
+        match s {
+            "nop" => Ok(OpKind::Nop),
+            "acc" => Ok(OpKind::Acc),
+            "jmp" => Ok(OpKind::Jmp),
+            _ => Err(format!("Invalid operation \"{}\"", s)),
+        }
+    }
 }
 
-#[derive(Clone, Copy)]
-enum Operation {
-    NOP(i32), // having an arg for NOP is important for code fixing
-    ACC(i32),
-    JMP(i32)
+impl fmt::Display for OpKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            OpKind::Nop => "nop",
+            OpKind::Acc => "acc",
+            OpKind::Jmp => "jmp",
+        })
+    }
 }
 
 impl Program {
-    fn new(assembly_filename: &str) -> Program {
+    fn new(code: &str) -> Result<Program, AoCError> {
         lazy_static! {
-            // (?m) enables "multiline mode":
             static ref INSTRUCTION: Regex = Regex::new(
-                r"(?m)^(?P<op>\w+) (?P<arg>[\+\-]?\d+)$"
+                r"^(?P<op>\w+) (?P<arg>[\+\-]?\d+)$"
             ).unwrap();
         }
 
-        let code = std::fs::read_to_string(&assembly_filename)
-            .expect("Could not read source assembly code.");
-        let mut instructions: Vec<Instruction> = Vec::new();
-        for instr_cap in INSTRUCTION.captures_iter(&code) {
-            let op: Operation;
-            let arg: i32 = instr_cap.name("arg").unwrap().as_str()
-                .parse::<i32>().expect("Could not parse arg to i32");
-            match instr_cap.name("op").unwrap().as_str() {
-                "nop" => { op = Operation::NOP(arg); },
-                "acc" => { op = Operation::ACC(arg); },
-                "jmp" => { op = Operation::JMP(arg); },
-                op => { panic!("Invalid operation \"{}\"", op); }
-            }
-            instructions.push(Instruction {
-                op,
-                call_count: 0
-            });
+        let mut instructions: Vec<Operation> = Vec::new();
+        for (i, line) in parse::lines(code) {
+            let instr_cap = INSTRUCTION.captures(line.trim_end())
+                .ok_or_else(|| AoCError::parse(
+                    i, 1, "Expected \"<op> <arg>\", e.g. \"jmp -3\""
+                ))?;
+            let arg = instr_cap.name("arg").unwrap();
+            let arg: i32 = parse::value(arg.as_str(), i, arg.start() + 1)?;
+            let op: Operation = match instr_cap.name("op").unwrap().as_str().parse() {
+                Ok(OpKind::Nop) => Operation::Nop(arg),
+                Ok(OpKind::Acc) => Operation::Acc(arg),
+                Ok(OpKind::Jmp) => Operation::Jmp(arg),
+                Err(msg) => return Err(AoCError::parse(i, 1, msg)),
+            };
+            instructions.push(op);
         }
 
-        Program {
+        Ok(Program {
             instructions
-        }
+        })
     }
 
-    fn compute(&mut self) -> ProgramResult {
-        self.reset();
-        let mut acc: i32 = 0; // the accumulator
-        let mut pc: i32 = 0; // the program counter
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
 
-        loop {
-            if pc < 0 || pc > self.instructions.len() as i32 {
-                return ProgramResult::PcOutOfBounds(pc, acc);
-            }
-            if pc == self.instructions.len() as i32 {
-                return ProgramResult::Terminated(acc);
-            }
-            let instruction: &mut Instruction = self.instructions
-                .get_mut(pc as usize).unwrap();
-            if instruction.call_count > 0 {
-                return ProgramResult::FoundLoop(pc as usize, acc);
-            }
-            instruction.call_count += 1;
-            match instruction.op {
-                Operation::NOP(_) => { pc += 1; },
-                Operation::ACC(arg) => { pc += 1; acc += arg; },
-                Operation::JMP(arg) => { pc += arg; }
-            }
-        }
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
     }
 
-    fn reset(&mut self) {
-        for instruction in self.instructions.iter_mut() {
-            instruction.call_count = 0;
-        }
+    pub fn get(&self, pc: usize) -> Option<Operation> {
+        self.instructions.get(pc).copied()
     }
 
     /// Returns the first program that terminates successfully
     /// if exactly one NOP/JMP is flipped, plus the final acc value
     /// and the 0-based index of the corrupted instruction.
-    fn get_fixed_program(&self) -> Option<(Program, i32, usize)> {
+    pub fn get_fixed_program(&self) -> Option<(Program, i32, usize)> {
         for i in 0..self.instructions.len() {
             let mut program: Program = self.clone();
-            match program.instructions[i].op {
-                Operation::NOP(arg) => {
-                    program.instructions[i].op = Operation::JMP(arg);
+            match program.instructions[i] {
+                Operation::Nop(arg) => {
+                    program.instructions[i] = Operation::Jmp(arg);
                 },
-                Operation::JMP(arg) => {
-                    program.instructions[i].op = Operation::NOP(arg);
+                Operation::Jmp(arg) => {
+                    program.instructions[i] = Operation::Nop(arg);
                 },
                 _ => { continue; }
             }
-            if let ProgramResult::Terminated(acc) = program.compute() {
+            let mut vm = Vm::new(&program);
+            if let Stop::Halted(Halt::Terminated) = vm.run() {
+                let acc = vm.acc();
                 return Some((program, acc, i));
             }
         }
         None
     }
 }
+
+/// Where the VM pauses before executing an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    Pc(usize),
+    Kind(OpKind),
+}
+
+impl Breakpoint {
+    fn matches(self, pc: usize, op: Operation) -> bool {
+        match self {
+            Breakpoint::Pc(at) => at == pc,
+            Breakpoint::Kind(kind) => kind == op.kind(),
+        }
+    }
+}
+
+impl FromStr for Breakpoint {
+    type Err = String;
+
+    /// Parses a pc like "7" or an operation like "jmp".
+    fn from_str(s: &str) -> Result<Breakpoint, String> {
+        match s.parse::<usize>() {
+            Ok(pc) => Ok(Breakpoint::Pc(pc)),
+            Err(_) => s.parse().map(Breakpoint::Kind).map_err(|_| {
+                format!("Expected a pc or one of nop/acc/jmp, not \"{}\"", s)
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Breakpoint::Pc(pc) => write!(f, "pc {}", pc),
+            Breakpoint::Kind(kind) => write!(f, "{}", kind),
+        }
+    }
+}
+
+/// When the VM stops for good, besides running off the program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HaltPolicy {
+    /// Halt before an instruction would be executed a second time.
+    pub on_repeat: bool,
+    /// Halt after this many steps.
+    pub max_steps: Option<usize>,
+}
+
+impl Default for HaltPolicy {
+    fn default() -> HaltPolicy {
+        HaltPolicy { on_repeat: true, max_steps: None }
+    }
+}
+
+/// Why the VM can't take another step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Halt {
+    /// The pc is right after the last instruction.
+    Terminated,
+    /// The pc is somewhere else outside of the program.
+    OutOfBounds,
+    /// The instruction at the pc has already been executed.
+    Repeat,
+    /// The step limit of the policy is reached.
+    StepLimit,
+    /// The instruction at the pc would overflow the accumulator.
+    Overflow,
+}
+
+impl fmt::Display for Halt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Halt::Terminated => "terminated",
+            Halt::OutOfBounds => "pc out of bounds",
+            Halt::Repeat => "instruction executed before",
+            Halt::StepLimit => "step limit reached",
+            Halt::Overflow => "accumulator would overflow",
+        })
+    }
+}
+
+/// Why [`Vm::run`] returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    Breakpoint(Breakpoint),
+    Halted(Halt),
+}
+
+/// One executed instruction, with the state after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub step: usize,
+    pub pc: usize,
+    pub op: Operation,
+    pub acc: i32,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>6}  {:>4}: {:<10} acc {}", self.step, self.pc, self.op, self.acc)
+    }
+}
+
+/// The handheld console, executing a program one instruction at a time.
+#[derive(Clone, Debug)]
+pub struct Vm<'a> {
+    program: &'a Program,
+    pc: i64,
+    acc: i32,
+    steps: usize,
+    visits: Vec<usize>,
+    policy: HaltPolicy,
+    breakpoints: Vec<Breakpoint>,
+    trace: Option<Vec<TraceEntry>>,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(program: &'a Program) -> Vm<'a> {
+        Vm {
+            program,
+            pc: 0,
+            acc: 0,
+            steps: 0,
+            visits: vec![0; program.len()],
+            policy: HaltPolicy::default(),
+            breakpoints: Vec::new(),
+            trace: None,
+        }
+    }
+
+    pub fn with_policy(mut self, policy: HaltPolicy) -> Vm<'a> {
+        self.policy = policy;
+        self
+    }
+
+    /// Keeps a trace of all executed instructions from now on.
+    pub fn with_trace(mut self) -> Vm<'a> {
+        self.trace.get_or_insert_with(Vec::new);
+        self
+    }
+
+    pub fn program(&self) -> &'a Program {
+        self.program
+    }
+
+    /// The program counter, which can point outside of the program.
+    pub fn pc(&self) -> i64 {
+        self.pc
+    }
+
+    pub fn acc(&self) -> i32 {
+        self.acc
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// How often the instruction at `pc` has been executed.
+    pub fn visits(&self, pc: usize) -> usize {
+        self.visits.get(pc).copied().unwrap_or(0)
+    }
+
+    pub fn policy(&self) -> HaltPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: HaltPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn trace(&self) -> Option<&[TraceEntry]> {
+        self.trace.as_deref()
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Adds a breakpoint, returning false if it was already there.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        if self.breakpoints.contains(&breakpoint) {
+            return false;
+        }
+        self.breakpoints.push(breakpoint);
+        true
+    }
+
+    /// Removes a breakpoint, returning false if there was none.
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        let len = self.breakpoints.len();
+        self.breakpoints.retain(|&b| b != breakpoint);
+        self.breakpoints.len() < len
+    }
+
+    /// Starts over at the first instruction, keeping the policy, the
+    /// breakpoints and whether there is a trace.
+    pub fn reset(&mut self) {
+        self.pc = 0;
+        self.acc = 0;
+        self.steps = 0;
+        self.visits.iter_mut().for_each(|visits| *visits = 0);
+        if let Some(trace) = &mut self.trace {
+            trace.clear();
+        }
+    }
+
+    /// The instruction at the pc, if the pc is inside of the program.
+    pub fn current(&self) -> Option<(usize, Operation)> {
+        usize::try_from(self.pc).ok()
+            .and_then(|pc| self.program.get(pc).map(|op| (pc, op)))
+    }
+
+    /// Returns why the next step can't be taken, if it can't.
+    pub fn halt(&self) -> Option<Halt> {
+        let (pc, op) = match self.current() {
+            Some(current) => current,
+            None if self.pc == self.program.len() as i64 => return Some(Halt::Terminated),
+            None => return Some(Halt::OutOfBounds),
+        };
+        if self.policy.on_repeat && self.visits[pc] > 0 {
+            Some(Halt::Repeat)
+        } else if self.policy.max_steps.is_some_and(|max| self.steps >= max) {
+            Some(Halt::StepLimit)
+        } else if matches!(op, Operation::Acc(arg) if self.acc.checked_add(arg).is_none()) {
+            Some(Halt::Overflow)
+        } else {
+            None
+        }
+    }
+
+    /// Executes the instruction at the pc, or returns why it can't.
+    pub fn step(&mut self) -> Result<TraceEntry, Halt> {
+        if let Some(halt) = self.halt() {
+            return Err(halt);
+        }
+        let (pc, op) = self.current().unwrap();
+        self.visits[pc] += 1;
+        match op {
+            Operation::Nop(_) => { self.pc += 1; },
+            Operation::Acc(arg) => { self.pc += 1; self.acc += arg; },
+            Operation::Jmp(arg) => { self.pc += arg as i64; }
+        }
+        self.steps += 1;
+        let entry = TraceEntry { step: self.steps, pc, op, acc: self.acc };
+        if let Some(trace) = &mut self.trace {
+            trace.push(entry);
+        }
+        Ok(entry)
+    }
+
+    /// Steps until the VM halts or reaches a breakpoint. The instruction
+    /// at the pc is always executed, so that running again after stopping
+    /// at a breakpoint moves on.
+    pub fn run(&mut self) -> Stop {
+        loop {
+            if let Err(halt) = self.step() {
+                return Stop::Halted(halt);
+            }
+            if let Some((pc, op)) = self.current() {
+                if let Some(&b) = self.breakpoints.iter().find(|b| b.matches(pc, op)) {
+                    return Stop::Breakpoint(b);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6\n";
+
+    #[test]
+    fn test_vm() {
+        let program = Program::new(EXAMPLE).unwrap();
+        let mut vm = Vm::new(&program).with_trace();
+        assert_eq!(vm.run(), Stop::Halted(Halt::Repeat));
+        assert_eq!((vm.pc(), vm.acc(), vm.steps()), (1, 5, 7));
+        let pcs: Vec<usize> = vm.trace().unwrap().iter().map(|entry| entry.pc).collect();
+        assert_eq!(pcs, vec![0, 1, 2, 6, 7, 3, 4]);
+        assert_eq!(vm.trace().unwrap()[3].to_string(), "     4     6: acc +1     acc 2");
+
+        vm.reset();
+        vm.set_policy(HaltPolicy { on_repeat: false, max_steps: Some(10) });
+        assert_eq!(vm.run(), Stop::Halted(Halt::StepLimit));
+        assert_eq!((vm.pc(), vm.visits(1)), (7, 2));
+
+        let (fixed, acc, i) = program.get_fixed_program().unwrap();
+        assert_eq!((acc, i, fixed.get(i)), (8, 7, Some(Operation::Nop(-4))));
+        let mut vm = Vm::new(&fixed);
+        assert_eq!(vm.run(), Stop::Halted(Halt::Terminated));
+        assert_eq!(vm.step(), Err(Halt::Terminated));
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let program = Program::new(EXAMPLE).unwrap();
+        let mut vm = Vm::new(&program);
+        assert!(vm.add_breakpoint("jmp".parse().unwrap()));
+        assert!(vm.add_breakpoint("7".parse().unwrap()));
+        assert!(!vm.add_breakpoint(Breakpoint::Pc(7)));
+        assert_eq!(vm.run(), Stop::Breakpoint(Breakpoint::Kind(OpKind::Jmp)));
+        assert_eq!(vm.current(), Some((2, Operation::Jmp(4))));
+        assert_eq!(vm.run(), Stop::Breakpoint(Breakpoint::Kind(OpKind::Jmp)));
+        assert_eq!(vm.pc(), 7);
+        assert!(vm.remove_breakpoint(Breakpoint::Kind(OpKind::Jmp)));
+        assert_eq!(vm.run(), Stop::Halted(Halt::Repeat));
+        assert!("jump".parse::<Breakpoint>().is_err());
+
+        let program = Program::new("jmp -1\n").unwrap();
+        assert_eq!(Vm::new(&program).run(), Stop::Halted(Halt::OutOfBounds));
+
+        let program = Program::new("acc +2147483647\nacc +1\n").unwrap();
+        let mut vm = Vm::new(&program);
+        assert_eq!(vm.run(), Stop::Halted(Halt::Overflow));
+        assert_eq!((vm.pc(), vm.acc()), (1, i32::MAX));
+    }
+}
@@ -1,32 +1,59 @@
 use std::vec::Vec;
+use aoc_core::{AoCError, Answer, Params, Solution, parse};
 
-pub fn run_day09(puzzle_input: &str) {
-    let numbers: Vec<u64> = std::fs::read_to_string(&puzzle_input)
-        .expect(format!("Could not read file \"{}\".", puzzle_input).as_str())
-        .split('\n')
-        .filter_map(|line| line.parse::<u64>().ok())
-        .collect();
-    if let Some(non_sum_number) = find_non_sum_number(&numbers, 25) {
-        println!(
-            "First number that's not a sum as specified: {}",
-            non_sum_number
-        );
-        if let Some(summands) = find_contiguous_summands(
-            &numbers, non_sum_number
-        ) {
-            let min = summands.iter().min().unwrap();
-            let max = summands.iter().max().unwrap();
-            println!(
-                "Found contiguous sequence with minimum {} and maximum {} \
-                (in sum: {})",
-                min, max,
-                min + max
-            );
-        } else {
-            println!("Did not find a contiguous sequence.");
+pub struct Day09;
+
+/// The XMAS data, where each number after the preamble should be the sum
+/// of two of the `preamble_len` numbers before it.
+pub struct Xmas {
+    pub preamble_len: usize,
+    pub numbers: Vec<u64>,
+}
+
+const PREAMBLE_LEN: usize = 25;
+
+impl Solution for Day09 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 9;
+
+    type Input = Xmas;
+
+    fn parse(&self, input: &str) -> Result<Xmas, AoCError> {
+        self.parse_with(input, &Params::new())
+    }
+
+    /// The preamble is 25 numbers long, unless the "preamble" parameter
+    /// says otherwise, as for the examples of the puzzle.
+    fn parse_with(&self, input: &str, params: &Params) -> Result<Xmas, AoCError> {
+        let mut preamble_len = PREAMBLE_LEN;
+        for (name, &value) in params {
+            match name.as_str() {
+                "preamble" => {
+                    preamble_len = usize::try_from(value).ok()
+                        .filter(|&len| len >= 2)
+                        .ok_or_else(|| AoCError::malformed("The preamble needs at least two numbers"))?;
+                },
+                _ => return Err(AoCError::unknown_param(name)),
+            }
         }
-    } else {
-        println!("Did not find any non-sum number.");
+        Ok(Xmas { preamble_len, numbers: parse::numbers(input)? })
+    }
+
+    fn part1(&self, xmas: &Xmas) -> Result<Answer, AoCError> {
+        Ok(find_non_sum_number(&xmas.numbers, xmas.preamble_len)
+            .ok_or_else(|| AoCError::no_solution("Did not find any non-sum number"))?
+            .into())
+    }
+
+    fn part2(&self, xmas: &Xmas) -> Result<Answer, AoCError> {
+        let numbers = &xmas.numbers;
+        let non_sum_number = find_non_sum_number(numbers, xmas.preamble_len)
+            .ok_or_else(|| AoCError::no_solution("Did not find any non-sum number"))?;
+        let summands = find_contiguous_summands(numbers, non_sum_number)
+            .ok_or_else(|| AoCError::no_solution("Did not find a contiguous sequence"))?;
+        let min = summands.iter().min().unwrap();
+        let max = summands.iter().max().unwrap();
+        Ok((min + max).into())
     }
 }
 
@@ -50,14 +77,14 @@ fn find_non_sum_number(
                 }
             }
         }
-        return None
+        None
     }
     for i in preamble_len..numbers.len() {
-        if let None = find_summands(numbers, i, preamble_len) {
+        if find_summands(numbers, i, preamble_len).is_none() {
             return Some(numbers[i]);
         }
     }
-    return None
+    None
 }
 
 fn find_contiguous_summands(
@@ -70,7 +97,7 @@ fn find_contiguous_summands(
             *acc += x;
             Some(*acc)
         }).collect();
-    for start in 0..(numbers.len()-1) {
+    for start in 0..numbers.len().saturating_sub(1) {
         let sum_to_start: u64 = cumsum[start];
         for end in (start+1)..=numbers.len() {
             if cumsum[end-1] - sum_to_start == desired_sum {
@@ -1,36 +1,43 @@
 use std::vec::Vec;
 use std::collections::{HashMap};
+use aoc_core::{AoCError, Answer, Solution, debug, parse};
 
-pub fn run_day10(puzzle_input: &str) {
-    let mut adapters: Vec<u32> = std::fs::read_to_string(&puzzle_input)
-        .expect(format!("Could not read file \"{}\".", puzzle_input).as_str())
-        .split('\n')
-        .filter_map(|line| line.parse::<u32>().ok())
-        .collect();
-    adapters.push(0); // also count the outlet
-    adapters.sort();
-    let built_in_adapter = adapters.last().unwrap() + 3;
-    adapters.push(built_in_adapter);
-    let mut joltage_diffs: HashMap<u32, i32> = HashMap::new();
-    for (prev, adapter) in adapters.iter().zip(&adapters[1..]) {
-        let diff = adapter - prev;
-        if let Some(counter) = joltage_diffs.get_mut(&diff) {
-            *counter += 1;
-        } else {
-            joltage_diffs.insert(diff, 1);
+pub struct Day10;
+
+impl Solution for Day10 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 10;
+
+    /// The sorted adapter chain, including the outlet and the built-in
+    /// adapter.
+    type Input = Vec<u32>;
+
+    fn parse(&self, input: &str) -> Result<Vec<u32>, AoCError> {
+        let mut adapters: Vec<u32> = parse::numbers(input)?;
+        adapters.push(0); // also count the outlet
+        adapters.sort();
+        let built_in_adapter = adapters.last().unwrap() + 3;
+        adapters.push(built_in_adapter);
+        Ok(adapters)
+    }
+
+    fn part1(&self, adapters: &Vec<u32>) -> Result<Answer, AoCError> {
+        let mut joltage_diffs: HashMap<u32, u32> = HashMap::new();
+        for (prev, adapter) in adapters.iter().zip(&adapters[1..]) {
+            *joltage_diffs.entry(adapter - prev).or_insert(0) += 1;
+        }
+        Ok((joltage_diffs.get(&1).unwrap_or(&0)
+            * joltage_diffs.get(&3).unwrap_or(&0)).into())
+    }
+
+    fn part2(&self, adapters: &Vec<u32>) -> Result<Answer, AoCError> {
+        if !adapter_chain_is_valid(adapters, 3) {
+            return Err(AoCError::no_solution(
+                "The adapters cannot be chained with at most 3 jolts difference"
+            ));
         }
+        Ok(count_arrangements(adapters, 3).into())
     }
-    println!("Adapters: {:?}", adapters);
-    println!("Joltage differences: {:?}", &joltage_diffs);
-    println!(
-        "1-jolt differences * 3-jolt differences: {}",
-        joltage_diffs.get(&1).unwrap_or(&0)
-        * joltage_diffs.get(&3).unwrap_or(&0)
-    );
-    println!(
-        "Number of possible arrangements: {}",
-        count_arrangements(&adapters, 3)
-    );
 }
 
 fn adapter_chain_is_valid(adapters: &[u32], tolerance: u32) -> bool {
@@ -38,7 +45,7 @@ fn adapter_chain_is_valid(adapters: &[u32], tolerance: u32) -> bool {
         || !adapters.iter().is_sorted()
         || adapters.first().unwrap() != &0
     { return false; }
-    adapter_subchain_is_valid(&adapters, tolerance)
+    adapter_subchain_is_valid(adapters, tolerance)
 }
 
 fn adapter_subchain_is_valid(adapters: &[u32], tolerance: u32) -> bool {
@@ -56,15 +63,15 @@ fn adapter_subchain_is_valid(adapters: &[u32], tolerance: u32) -> bool {
 /// tolerance, then brute-force only the sub-chains.
 fn count_arrangements(adapters: &[u32], tolerance: u32) -> u64 {
     assert!(tolerance > 1);
-    assert!(adapter_chain_is_valid(&adapters, tolerance));
+    assert!(adapter_chain_is_valid(adapters, tolerance));
     let mut count: u64 = 1;
     let mut prev_subchain_end: usize = 0;
-    println!("New chain: {:?}", &adapters);
+    debug!("New chain: {:?}", &adapters);
     for (i, (prev, adapter)) in adapters.iter().zip(
         &adapters[1..]
     ).enumerate() {
         if adapter - prev == tolerance {
-            println!("subchain {} to {}", prev_subchain_end, i);
+            debug!("subchain {} to {}", prev_subchain_end, i);
             count *= count_arrangements_bf(
                 &adapters[prev_subchain_end..=i+1],
                 tolerance,
@@ -83,11 +90,11 @@ fn count_arrangements_bf(
     start_i: usize
 ) -> u64 {
     if adapters.len() <= 2 { return 1; }
-    if start_i <= 0 || start_i >= adapters.len() - 1 {
+    if start_i == 0 || start_i >= adapters.len() - 1 {
         panic!("start_i={}, len={}, {:?}", start_i, adapters.len(), &adapters);
     }
     if adapters.len() - start_i < 2 { return 0; }
-    if !adapter_subchain_is_valid(&adapters, tolerance) { return 0; }
+    if !adapter_subchain_is_valid(adapters, tolerance) { return 0; }
     let mut count: u64 = 1; // without leaving out any adapters
 
     // We may only leave any but the first or the last adapter
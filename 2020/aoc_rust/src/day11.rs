@@ -1,40 +1,63 @@
-extern crate itertools;
-use std::vec::Vec;
-use itertools::Itertools;
-
-pub fn run_day11(puzzle_input: &str) {
-    let rows = SeatLayoutRuleOne::get_rows_from_str(
-        std::fs::read_to_string(&puzzle_input)
-            .expect(format!(
-                "Could not read file \"{}\".",
-                puzzle_input
-            ).as_str()).as_str()
-    );
-
-    let seat_layout_rule1 = SeatLayoutRuleOne{ rows: rows.clone() };
-    println!(
-        "Number of occupied seats rule one: {}",
-        run_to_convergence(&seat_layout_rule1).count_occupied()
-    );
-
-    let seat_layout_rule2 = SeatLayoutRuleTwo{ rows };
-    println!(
-        "Number of occupied seats rule two: {}",
-        run_to_convergence(&seat_layout_rule2).count_occupied()
-    );
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use aoc_core::{AoCError, Answer, Grid, Solution, debug};
+use aoc_core::grid::{DIRECTIONS_4, DIRECTIONS_8, Pos};
+use aoc_core::life::LifeRule;
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 11;
+
+    type Input = Grid<SeatPosition>;
+
+    fn parse(&self, input: &str) -> Result<Grid<SeatPosition>, AoCError> {
+        Grid::parse_char_map(input, SeatPosition::from_char)
+    }
+
+    fn part1(&self, layout: &Grid<SeatPosition>) -> Result<Answer, AoCError> {
+        let rule = SeatRule::with_tolerance(Neighbors::Adjacent, 4);
+        Ok(count_occupied(&run_to_convergence(layout, &rule)?).into())
+    }
+
+    fn part2(&self, layout: &Grid<SeatPosition>) -> Result<Answer, AoCError> {
+        let rule = SeatRule::with_tolerance(Neighbors::Visible, 5);
+        Ok(count_occupied(&run_to_convergence(layout, &rule)?).into())
+    }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-enum SeatPosition {
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SeatPosition {
     Floor,
     Empty,
     Occupied
 }
 
 impl SeatPosition {
-    #[allow(dead_code)] // used in tests
-    fn to_string(&self) -> String {
-        String::from(match *self {
+    fn from_char(c: char) -> Option<SeatPosition> {
+        match c {
+            '.' => Some(SeatPosition::Floor),
+            'L' => Some(SeatPosition::Empty),
+            '#' => Some(SeatPosition::Occupied),
+            _ => None,
+        }
+    }
+
+    /// The colour of the position in images of the layout.
+    pub fn color(&self) -> [u8; 3] {
+        match self {
+            SeatPosition::Floor => [0x30, 0x30, 0x30],
+            SeatPosition::Empty => [0x3c, 0xb3, 0x71],
+            SeatPosition::Occupied => [0xdc, 0x14, 0x3c],
+        }
+    }
+}
+
+impl fmt::Display for SeatPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
             SeatPosition::Floor => ".",
             SeatPosition::Empty => "L",
             SeatPosition::Occupied => "#"
@@ -42,243 +65,793 @@ impl SeatPosition {
     }
 }
 
-#[derive(Clone, PartialEq)]
-struct SeatLayoutRuleOne {
-    rows: Vec<Vec<SeatPosition>>
+/// The shape of the room.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Topology {
+    /// A rectangle with nothing beyond its edges.
+    Bounded,
+    /// A rectangle that wraps around at its edges, so that the first and
+    /// the last row and column are next to each other.
+    Torus,
+    /// A bounded grid of hexagons in axial coordinates: every row is
+    /// shifted half a cell to the left of the row above, so that a cell's
+    /// neighbours are left, right, above, below, above right and below left.
+    Hex,
 }
 
-impl SeatLayout for SeatLayoutRuleOne {
-    fn get_rows(&self) -> &Vec<Vec<SeatPosition>> {
-        &self.rows
+/// The six directions on a hex grid, clockwise starting with the top left.
+pub const HEX_DIRECTIONS: [(isize, isize); 6] = [(0, -1), (1, -1), (1, 0), (0, 1), (-1, 1), (-1, 0)];
+
+impl Topology {
+    /// The directions towards the adjacent cells.
+    fn directions(self) -> &'static [(isize, isize)] {
+        match self {
+            Topology::Hex => &HEX_DIRECTIONS,
+            _ => &DIRECTIONS_8,
+        }
     }
 
-    fn get_rows_mut(&mut self) -> &mut Vec<Vec<SeatPosition>> {
-        &mut self.rows
+    /// The directions towards the cells that share an edge with a cell.
+    fn orthogonal_directions(self) -> &'static [(isize, isize)] {
+        match self {
+            Topology::Hex => &HEX_DIRECTIONS,
+            _ => &DIRECTIONS_4,
+        }
+    }
+
+    /// The number of steps to a cell `(dx, dy)` away.
+    fn distance(self, (dx, dy): (isize, isize)) -> usize {
+        match self {
+            Topology::Hex => (dx.unsigned_abs() + dy.unsigned_abs() + (dx + dy).unsigned_abs()) / 2,
+            _ => dx.unsigned_abs().max(dy.unsigned_abs()),
+        }
+    }
+
+    /// The position `(dx, dy)` away from `pos`, if it is in the room.
+    fn step(self, layout: &Grid<SeatPosition>, pos: Pos, offset: (isize, isize)) -> Option<Pos> {
+        match self {
+            Topology::Torus => Some(layout.step_wrapping(pos, offset)),
+            _ => layout.step(pos, offset),
+        }
     }
 }
 
-#[derive(Clone, PartialEq)]
-struct SeatLayoutRuleTwo {
-    rows: Vec<Vec<SeatPosition>>
+/// Which seats count as neighbours of a seat.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Neighbors {
+    /// The seats that share an edge with the seat (von Neumann
+    /// neighbourhood): four on a square grid, six on a hex grid.
+    VonNeumann,
+    /// The adjacent seats (Moore neighbourhood): eight on a square grid,
+    /// six on a hex grid.
+    Adjacent,
+    /// The seats within the given number of steps in any direction.
+    Radius(usize),
+    /// The first seat in each direction, looking across the floor.
+    Visible,
+    /// The first seat in each direction within the given distance.
+    VisibleWithin(usize),
 }
 
-impl SeatLayout for SeatLayoutRuleTwo {
-    fn get_rows(&self) -> &Vec<Vec<SeatPosition>> {
-        &self.rows
+impl fmt::Display for Neighbors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Neighbors::VonNeumann => write!(f, "von-neumann"),
+            Neighbors::Adjacent => write!(f, "adjacent"),
+            Neighbors::Radius(radius) => write!(f, "radius:{}", radius),
+            Neighbors::Visible => write!(f, "visible"),
+            Neighbors::VisibleWithin(distance) => write!(f, "visible:{}", distance),
+        }
     }
+}
 
-    fn get_rows_mut(&mut self) -> &mut Vec<Vec<SeatPosition>> {
-        &mut self.rows
+fn parse_neighbors(s: &str) -> Option<Neighbors> {
+    match s.split_once(':') {
+        None => match s {
+            "von-neumann" => Some(Neighbors::VonNeumann),
+            "adjacent" | "moore" => Some(Neighbors::Adjacent),
+            "visible" => Some(Neighbors::Visible),
+            _ => None,
+        },
+        Some(("radius", radius)) => radius.parse().ok().map(Neighbors::Radius),
+        Some(("visible", distance)) => distance.parse().ok().map(Neighbors::VisibleWithin),
+        Some(_) => None,
     }
 }
 
-impl SeatingRule for SeatLayoutRuleOne {
-    fn count_occupied_neighbors(&self, row: usize, col: usize) -> usize {
-        let mut occupied_neighbors = 0;
-        for (i, j) in (0..=2).cartesian_product(0..=2) {
-            if i == 1 && j == 1 { continue; }
-            if let Some(neighbor) = self.get(
-                    (row + i) as i32 - 1,
-                    (col + j) as i32 - 1
-            ) {
-                occupied_neighbors +=
-                    if neighbor == &SeatPosition::Occupied {1} else {0};
-            }
+/// How people choose their seats: a life-like rule where occupied seats
+/// are active and the floor never changes.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SeatRule {
+    pub topology: Topology,
+    pub neighbors: Neighbors,
+    pub rule: LifeRule,
+}
+
+impl SeatRule {
+    /// People take empty seats without occupied neighbours and leave if
+    /// `tolerance` or more neighbours are occupied, i.e. the rule is
+    /// `B0/S0..tolerance-1`.
+    pub fn with_tolerance(neighbors: Neighbors, tolerance: usize) -> SeatRule {
+        let survival: Vec<usize> = (0..tolerance).collect();
+        SeatRule { topology: Topology::Bounded, neighbors, rule: LifeRule::new(&[0], &survival) }
+    }
+
+    /// The positions of the neighbours of `pos`, sorted. Each one is
+    /// listed once, and `pos` itself never is, even if the neighbourhood
+    /// wraps around a small torus. Floor tiles may be included.
+    fn neighbor_positions(&self, layout: &Grid<SeatPosition>, pos: Pos) -> Vec<Pos> {
+        let mut neighbors: Vec<Pos> = Vec::new();
+        self.for_each_neighbor(layout, pos, |neighbor| neighbors.push(neighbor));
+        neighbors.retain(|&neighbor| neighbor != pos);
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+
+    /// Calls `f` with the position of every neighbour of `pos`. Only on a
+    /// torus can this include the same position twice, or `pos` itself.
+    fn for_each_neighbor<F>(&self, layout: &Grid<SeatPosition>, pos: Pos, f: F)
+    where F: FnMut(Pos) {
+        let topology = self.topology;
+        let step = |offset: (isize, isize)| topology.step(layout, pos, offset);
+        match self.neighbors {
+            Neighbors::VonNeumann => topology.orthogonal_directions().iter()
+                .filter_map(|&dir| step(dir))
+                .for_each(f),
+            Neighbors::Adjacent => topology.directions().iter()
+                .filter_map(|&dir| step(dir))
+                .for_each(f),
+            Neighbors::Radius(radius) => {
+                let r = radius as isize;
+                (-r..=r).flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+                    .filter(|&offset| offset != (0, 0) && topology.distance(offset) <= radius)
+                    .filter_map(step)
+                    .for_each(f)
+            },
+            Neighbors::Visible => topology.directions().iter()
+                .filter_map(|&dir| self.first_visible_seat(layout, pos, dir, None))
+                .for_each(f),
+            Neighbors::VisibleWithin(distance) => topology.directions().iter()
+                .filter_map(|&dir| self.first_visible_seat(layout, pos, dir, Some(distance)))
+                .for_each(f),
         }
-        occupied_neighbors
-    }
-
-    fn get_new_seat_pos(
-        occupied_neighbors: usize,
-        sp: SeatPosition
-    ) -> SeatPosition {
-        if sp == SeatPosition::Empty
-                && occupied_neighbors == 0 {
-            return SeatPosition::Occupied;
-        } else if sp == SeatPosition::Occupied
-                && occupied_neighbors >= 4 {
-            return SeatPosition::Empty;
+    }
+
+    /// The position of the first seat that is visible from `pos` in the
+    /// given direction, looking across the floor for at most
+    /// `max_distance` steps.
+    fn first_visible_seat(
+        &self,
+        layout: &Grid<SeatPosition>,
+        pos: Pos,
+        dir: (isize, isize),
+        max_distance: Option<usize>
+    ) -> Option<Pos> {
+        // On a torus, every ray gets back to `pos` within this many steps.
+        let max_distance = max_distance.unwrap_or(layout.width() * layout.height());
+        std::iter::successors(
+            self.topology.step(layout, pos, dir),
+            |&seat| self.topology.step(layout, seat, dir)
+        )
+            .take(max_distance)
+            .take_while(|&seat| seat != pos)
+            .find(|&seat| layout[seat] != SeatPosition::Floor)
+    }
+
+    fn count_occupied_neighbors(&self, layout: &Grid<SeatPosition>, pos: Pos) -> usize {
+        let occupied = |neighbor: &Pos| layout[*neighbor] == SeatPosition::Occupied;
+        if self.topology == Topology::Torus {
+            return self.neighbor_positions(layout, pos).iter().filter(|n| occupied(n)).count();
+        }
+        let mut count = 0;
+        self.for_each_neighbor(layout, pos, |neighbor| {
+            if occupied(&neighbor) { count += 1; }
+        });
+        count
+    }
+
+    fn get_new_seat_pos(&self, occupied_neighbors: usize, sp: SeatPosition) -> SeatPosition {
+        if sp == SeatPosition::Floor { return sp; }
+        match self.rule.next_state(sp == SeatPosition::Occupied, occupied_neighbors) {
+            true => SeatPosition::Occupied,
+            false => SeatPosition::Empty,
         }
-        sp
     }
 }
 
-impl SeatLayoutRuleTwo {
-    fn get_first_visible_seat_pos(
-        &self,
-        row: usize,
-        col: usize,
-        step_x: i32,
-        step_y: i32
-    ) -> Option<&SeatPosition> {
-        let mut x: i32 = row as i32;
-        let mut y: i32 = col as i32;
-        loop {
-            x += step_x;
-            y += step_y;
-            let current_seat_pos = self.get(x, y);
-            if let None = current_seat_pos { return None; }
-            else if let Some(SeatPosition::Occupied)
-                    | Some(SeatPosition::Empty) = current_seat_pos {
-                return current_seat_pos;
-            } // else will be SeatPosition::Floor
+impl fmt::Display for SeatRule {
+    /// Leaves out the topology if it is bounded.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.topology {
+            Topology::Bounded => (),
+            Topology::Torus => write!(f, "topology=torus,")?,
+            Topology::Hex => write!(f, "topology=hex,")?,
         }
+        write!(f, "neighbors={},rule={}", self.neighbors, self.rule)
     }
 }
 
-impl SeatingRule for SeatLayoutRuleTwo {
-    fn count_occupied_neighbors(&self, row: usize, col: usize) -> usize {
-        let mut occupied_neighbors = 0;
-        for (i, j) in (0..=2).cartesian_product(0..=2) {
-            if i == 1 && j == 1 { continue; }
-            let step_x: i32 = i - 1;
-            let step_y: i32 = j - 1;
-            if let Some(neighbor) = self.get_first_visible_seat_pos(
-                row, col, step_x, step_y
-            ) {
-                occupied_neighbors +=
-                    if neighbor == &SeatPosition::Occupied {1} else {0};
+impl FromStr for SeatRule {
+    type Err = AoCError;
+
+    /// Parses a comma-separated list of settings, e.g.
+    /// `neighbors=visible,tolerance=5` or
+    /// `topology=torus,neighbors=radius:2,rule=B3/S23`. The defaults are the
+    /// rules of part 1.
+    fn from_str(s: &str) -> Result<SeatRule, AoCError> {
+        let mut seat_rule = SeatRule::with_tolerance(Neighbors::Adjacent, 4);
+        for setting in s.split(',').map(|setting| setting.trim()).filter(|s| !s.is_empty()) {
+            let invalid = || AoCError::malformed(format!("Invalid seat rule setting \"{}\"", setting));
+            let (key, value) = setting.split_once('=').ok_or_else(invalid)?;
+            match (key.trim(), value.trim()) {
+                ("topology", "bounded") => seat_rule.topology = Topology::Bounded,
+                ("topology", "torus") => seat_rule.topology = Topology::Torus,
+                ("topology", "hex") => seat_rule.topology = Topology::Hex,
+                ("neighbors", neighbors) => {
+                    seat_rule.neighbors = parse_neighbors(neighbors).ok_or_else(invalid)?;
+                },
+                ("tolerance", tolerance) => {
+                    let tolerance = tolerance.parse().map_err(|_| invalid())?;
+                    seat_rule.rule = SeatRule::with_tolerance(seat_rule.neighbors, tolerance).rule;
+                },
+                ("rule", rule) => seat_rule.rule = rule.parse()?,
+                _ => return Err(invalid()),
             }
         }
-        occupied_neighbors
+        Ok(seat_rule)
+    }
+}
+
+pub fn count_occupied(layout: &Grid<SeatPosition>) -> usize {
+    layout.iter().filter(|&&seat_pos| seat_pos == SeatPosition::Occupied).count()
+}
+
+/// How many steps `run_to_convergence` takes at most before giving up.
+pub const MAX_STEPS: usize = 10_000;
+
+/// How a simulation ended.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    /// The layout after `steps` steps doesn't change anymore.
+    Converged { steps: usize },
+    /// From step `start` on, the same `length` layouts repeat forever.
+    Cycle { start: usize, length: usize },
+    /// The layout was still changing after `steps` steps.
+    MaxSteps { steps: usize },
+}
 
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Converged { steps } => write!(f, "converged after {} steps", steps),
+            Outcome::Cycle { start, length } => write!(
+                f, "entered a cycle of length {} at step {}", length, start
+            ),
+            Outcome::MaxSteps { steps } => write!(
+                f, "still changing after {} steps", steps
+            ),
+        }
     }
+}
+
+/// Applies the rule until the layout repeats or `max_steps` steps have
+/// been taken. Returns how it ended along with the last layout.
+pub fn simulate(
+    layout: &Grid<SeatPosition>,
+    rule: &SeatRule,
+    max_steps: usize
+) -> (Outcome, Grid<SeatPosition>) {
+    simulate_observed(layout, rule, max_steps, |_, _| ())
+}
 
-    fn get_new_seat_pos(
-        occupied_neighbors: usize,
-        sp: SeatPosition
-    ) -> SeatPosition {
-        if sp == SeatPosition::Empty
-                && occupied_neighbors == 0 {
-            return SeatPosition::Occupied;
-        } else if sp == SeatPosition::Occupied
-                && occupied_neighbors >= 5 {
-            return SeatPosition::Empty;
+/// Like `simulate`, but passes every distinct layout to `observe` along
+/// with its step, starting with the initial layout at step 0.
+pub fn simulate_observed<F>(
+    layout: &Grid<SeatPosition>,
+    rule: &SeatRule,
+    max_steps: usize,
+    mut observe: F
+) -> (Outcome, Grid<SeatPosition>)
+where F: FnMut(usize, &Grid<SeatPosition>) {
+    // The step at which each layout was seen first.
+    let mut seen: HashMap<Grid<SeatPosition>, usize> = HashMap::new();
+    let mut current = layout.clone();
+    for steps in 0..=max_steps {
+        if let Some(&start) = seen.get(&current) {
+            let outcome = match steps - start {
+                1 => Outcome::Converged { steps: start },
+                length => Outcome::Cycle { start, length },
+            };
+            return (outcome, current);
         }
-        sp
+        observe(steps, &current);
+        if steps == max_steps { break; }
+        let next = step(&current, rule);
+        seen.insert(current, steps);
+        current = next;
     }
+    (Outcome::MaxSteps { steps: max_steps }, current)
 }
 
-trait SeatingRule {
-    fn count_occupied_neighbors(&self, row: usize, col: usize) -> usize;
+/// Applies the rule until nothing changes anymore, or fails if the layout
+/// cycles or keeps changing for `MAX_STEPS` steps.
+pub fn run_to_convergence(
+    layout: &Grid<SeatPosition>,
+    rule: &SeatRule
+) -> Result<Grid<SeatPosition>, AoCError> {
+    let mut engine = SeatEngine::new(layout, rule);
+    let outcome = engine.run(MAX_STEPS);
+    converged_layout(&engine, outcome)
+}
+
+/// Like `run_to_convergence`, but passes every layout to `observe` along
+/// with its step, starting with the initial layout at step 0.
+pub fn run_to_convergence_observed<F>(
+    layout: &Grid<SeatPosition>,
+    rule: &SeatRule,
+    mut observe: F
+) -> Result<Grid<SeatPosition>, AoCError>
+where F: FnMut(usize, &Grid<SeatPosition>) {
+    let mut engine = SeatEngine::new(layout, rule);
+    let outcome = engine.run_observed(MAX_STEPS, |steps, engine| observe(steps, &engine.layout()));
+    converged_layout(&engine, outcome)
+}
 
-    fn get_new_seat_pos(
-        occupied_neighbors: usize,
-        sp: SeatPosition
-    ) -> SeatPosition;
+fn converged_layout(engine: &SeatEngine, outcome: Outcome) -> Result<Grid<SeatPosition>, AoCError> {
+    match outcome {
+        Outcome::Converged { steps } => {
+            debug!("Seats converged after {} steps", steps);
+            Ok(engine.layout())
+        },
+        outcome => Err(AoCError::no_solution(format!("The seats never settle: {}", outcome))),
+    }
 }
 
-trait SeatLayout {
-    fn get_rows(&self) -> &Vec<Vec<SeatPosition>>;
-    fn get_rows_mut(&mut self) -> &mut Vec<Vec<SeatPosition>>;
-
-    fn get_rows_from_str(layout_str: &str) -> Vec<Vec<SeatPosition>> {
-        layout_str
-            .split('\n')
-            .map(|line| line.chars().map(|c| {
-                match c {
-                    '.' => SeatPosition::Floor,
-                    'L' => SeatPosition::Empty,
-                    '#' => SeatPosition::Occupied,
-                    e => { panic!("Unrecognized SeatPosition {}", e); }
+/// Applies the rule once. With the `parallel` feature, the rows are
+/// computed on several threads.
+pub fn step(layout: &Grid<SeatPosition>, rule: &SeatRule) -> Grid<SeatPosition> {
+    #[cfg(feature = "parallel")]
+    return par_step(layout, rule);
+    #[cfg(not(feature = "parallel"))]
+    return serial_step(layout, rule);
+}
+
+#[cfg(any(test, not(feature = "parallel")))]
+fn serial_step(layout: &Grid<SeatPosition>, rule: &SeatRule) -> Grid<SeatPosition> {
+    let mut new_layout = layout.clone();
+    for (pos, &seat_pos) in layout.enumerate() {
+        let occupied_neighbors = rule.count_occupied_neighbors(layout, pos);
+        new_layout[pos] = rule.get_new_seat_pos(occupied_neighbors, seat_pos);
+    }
+    new_layout
+}
+
+#[cfg(feature = "parallel")]
+fn par_step(layout: &Grid<SeatPosition>, rule: &SeatRule) -> Grid<SeatPosition> {
+    use rayon::prelude::*;
+
+    let width = layout.width();
+    if width == 0 {
+        return layout.clone();
+    }
+    let mut cells = vec![SeatPosition::Floor; width * layout.height()];
+    cells.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+        for (x, cell) in row.iter_mut().enumerate() {
+            let occupied_neighbors = rule.count_occupied_neighbors(layout, (x, y));
+            *cell = rule.get_new_seat_pos(occupied_neighbors, layout[(x, y)]);
+        }
+    });
+    Grid::new(width, layout.height(), cells)
+}
+
+/// Simulates a layout with the same results as `step`, but faster: the
+/// neighbours of every seat are looked up once, the occupancy lives in two
+/// buffers that take turns instead of a new layout per step, and only seats
+/// next to a seat that changed in the previous step are re-evaluated. With
+/// the `parallel` feature, those seats are evaluated on several threads.
+///
+/// Instead of keeping every layout to detect cycles, the engine keeps a
+/// hash of the occupied seats that is updated along with the seats.
+#[derive(Clone)]
+pub struct SeatEngine {
+    layout: Grid<SeatPosition>,
+    rule: LifeRule,
+    /// The position of every seat, i.e. everything that isn't floor.
+    seats: Vec<Pos>,
+    /// The indices of the neighbouring seats of every seat. All
+    /// neighbourhoods are symmetric, so these are also the seats whose
+    /// neighbours change when a seat changes.
+    neighbors: Vec<Vec<usize>>,
+    current: Vec<bool>,
+    next: Vec<bool>,
+    /// The seats to re-evaluate in the next step.
+    dirty: Vec<usize>,
+    is_dirty: Vec<bool>,
+    /// A random key per seat, so that the XOR of the keys of the occupied
+    /// seats is a hash of the layout (Zobrist hashing).
+    keys: Vec<u64>,
+    hash: u64,
+}
+
+impl SeatEngine {
+    pub fn new(layout: &Grid<SeatPosition>, rule: &SeatRule) -> SeatEngine {
+        let seats: Vec<Pos> = layout.enumerate()
+            .filter(|&(_, &seat_pos)| seat_pos != SeatPosition::Floor)
+            .map(|(pos, _)| pos)
+            .collect();
+        let mut index: Grid<Option<usize>> = Grid::filled(layout.width(), layout.height(), None);
+        for (i, &pos) in seats.iter().enumerate() {
+            index[pos] = Some(i);
+        }
+        let neighbors: Vec<Vec<usize>> = seats.iter()
+            .map(|&pos| rule.neighbor_positions(layout, pos).into_iter()
+                .filter_map(|neighbor| index[neighbor])
+                .collect())
+            .collect();
+        let current: Vec<bool> = seats.iter()
+            .map(|&pos| layout[pos] == SeatPosition::Occupied)
+            .collect();
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+        let keys: Vec<u64> = seats.iter().map(|_| xorshift(&mut state)).collect();
+        let hash = keys.iter().zip(&current)
+            .filter(|&(_, &occupied)| occupied)
+            .fold(0, |hash, (key, _)| hash ^ key);
+        SeatEngine {
+            layout: layout.clone(),
+            rule: rule.rule.clone(),
+            next: current.clone(),
+            current,
+            dirty: (0..seats.len()).collect(),
+            is_dirty: vec![true; seats.len()],
+            seats,
+            neighbors,
+            keys,
+            hash,
+        }
+    }
+
+    /// The next state of each dirty seat.
+    fn next_states(&self) -> Vec<bool> {
+        let next_state = |&seat: &usize| {
+            let occupied_neighbors = self.neighbors[seat].iter()
+                .filter(|&&neighbor| self.current[neighbor])
+                .count();
+            self.rule.next_state(self.current[seat], occupied_neighbors)
+        };
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            self.dirty.par_iter().with_min_len(1024).map(next_state).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        self.dirty.iter().map(next_state).collect()
+    }
+
+    /// Applies the rule once. Returns whether any seat changed.
+    pub fn step(&mut self) -> bool {
+        let mut changed: Vec<usize> = Vec::new();
+        for (&seat, next) in self.dirty.iter().zip(self.next_states()) {
+            self.is_dirty[seat] = false;
+            self.next[seat] = next;
+            if self.next[seat] != self.current[seat] {
+                changed.push(seat);
+            }
+        }
+        std::mem::swap(&mut self.current, &mut self.next);
+        // Outside of the changed seats, both buffers agree again.
+        for &seat in &changed {
+            self.next[seat] = self.current[seat];
+            self.hash ^= self.keys[seat];
+        }
+
+        self.dirty.clear();
+        for &seat in &changed {
+            for &affected in std::iter::once(&seat).chain(&self.neighbors[seat]) {
+                if !self.is_dirty[affected] {
+                    self.is_dirty[affected] = true;
+                    self.dirty.push(affected);
                 }
-            }).collect::<Vec<SeatPosition>>())
-            .collect::<Vec<Vec<SeatPosition>>>()
-    }
-
-    fn to_string(&self) -> String {
-        String::from(
-            self.get_rows().iter().map(|row| {
-                row.iter().enumerate().map(|(i, seat_pos)| {
-                    if i == row.len() - 1 {
-                        format!("{}\n", seat_pos.to_string())
-                    } else {
-                        seat_pos.to_string()
-                    }
-                }).join("")
-            }).join("")
-        )
+            }
+        }
+        !changed.is_empty()
     }
 
-    fn count_occupied(&self) -> usize {
-        let mut count: usize = 0;
-        for row in self.get_rows() {
-            for seat in row {
-                if let SeatPosition::Occupied = seat {
-                    count += 1;
+    /// Steps until the layout repeats or `max_steps` steps have been taken,
+    /// with the same outcome as `simulate`. Cycles are found by the hash of
+    /// the layout, and a layout with a hash seen before is compared against
+    /// the earlier one before reporting a cycle.
+    pub fn run(&mut self, max_steps: usize) -> Outcome {
+        self.run_observed(max_steps, |_, _| ())
+    }
+
+    /// Like `run`, but passes the engine to `observe` at every distinct
+    /// layout along with its step, starting with the initial layout at
+    /// step 0, like `simulate_observed`.
+    pub fn run_observed<F>(&mut self, max_steps: usize, mut observe: F) -> Outcome
+    where F: FnMut(usize, &SeatEngine) {
+        let initial = self.current.clone();
+        // The steps at which each hash was seen, which only differ for
+        // layouts with colliding hashes.
+        let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+        for steps in 0..=max_steps {
+            if let Some(starts) = seen.get(&self.hash) {
+                if let Some(start) = self.find_earlier(&initial, starts) {
+                    return Outcome::Cycle { start, length: steps - start };
                 }
             }
+            observe(steps, self);
+            if steps == max_steps { break; }
+            seen.entry(self.hash).or_default().push(steps);
+            if !self.step() {
+                return Outcome::Converged { steps };
+            }
         }
-        count
+        Outcome::MaxSteps { steps: max_steps }
     }
 
-    fn get(
-        &self,
-        row_i: i32,
-        col_i: i32
-    ) -> Option<&SeatPosition> {
-        if row_i < 0 || col_i < 0 { return None; }
-        self.get_rows().get(row_i as usize).and_then(|row| row.get(col_i as usize))
+    /// Returns the first of the ascending `steps` after which the layout
+    /// was the same as now, by replaying the steps from `initial`.
+    fn find_earlier(&self, initial: &[bool], steps: &[usize]) -> Option<usize> {
+        let mut replay = self.clone();
+        replay.current.copy_from_slice(initial);
+        replay.next.copy_from_slice(initial);
+        replay.dirty = (0..self.seats.len()).collect();
+        replay.is_dirty.iter_mut().for_each(|is_dirty| *is_dirty = true);
+        let mut replayed = 0;
+        steps.iter().copied().find(|&step| {
+            while replayed < step {
+                replay.step();
+                replayed += 1;
+            }
+            replay.current == self.current
+        })
     }
-}
 
-fn run_to_convergence<T>(
-    seat_layout: &T
-) -> T where
-        T: SeatingRule + Clone + PartialEq + SeatLayout {
-    let mut prev_layout = seat_layout.clone();
-    loop {
-        let new_layout = step(&prev_layout);
-        if new_layout == prev_layout { return new_layout; }
-        prev_layout = new_layout;
+    pub fn count_occupied(&self) -> usize {
+        self.current.iter().filter(|&&occupied| occupied).count()
     }
-}
 
-fn step<T>(
-    seat_layout: &T
-) -> T where
-        T: SeatingRule + Clone + SeatLayout {
-    let mut new_layout: T = seat_layout.clone();
-    for (row_i, row) in seat_layout.get_rows().iter().enumerate() {
-        for (col_i, seat_pos) in row.iter().enumerate() {
-            let occupied_neighbors = seat_layout.count_occupied_neighbors(
-                row_i, col_i);
-            new_layout.get_rows_mut()[row_i][col_i] =
-                T::get_new_seat_pos(occupied_neighbors, *seat_pos);
+    pub fn layout(&self) -> Grid<SeatPosition> {
+        let mut layout = self.layout.clone();
+        for (&pos, &occupied) in self.seats.iter().zip(&self.current) {
+            layout[pos] = if occupied { SeatPosition::Occupied } else { SeatPosition::Empty };
         }
+        layout
     }
-    new_layout
+}
+
+/// A random layout of empty seats with about one floor tile in five, the
+/// same for the same seed.
+pub fn generate_layout(width: usize, height: usize, seed: u64) -> Grid<SeatPosition> {
+    // xorshift must not start at 0
+    let mut state = seed | 1;
+    let cells: Vec<SeatPosition> = (0..width * height)
+        .map(|_| match xorshift(&mut state) % 5 {
+            0 => SeatPosition::Floor,
+            _ => SeatPosition::Empty,
+        })
+        .collect();
+    Grid::new(width, height, cells)
+}
+
+/// The next number of the xorshift64 generator.
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc_core::frames::FrameRecorder;
+
+    fn layout(s: &str) -> Grid<SeatPosition> {
+        Day11.parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_first_visible_seat() {
+        let sl = layout(
+            ".L.L\n\
+             ....\n\
+             .#.L\n"
+        );
+        let rule = SeatRule::with_tolerance(Neighbors::Visible, 5);
+        assert_eq!(rule.first_visible_seat(&sl, (1, 0), (0, 1), None), Some((1, 2)));
+        assert_eq!(rule.first_visible_seat(&sl, (1, 0), (1, 1), None), Some((3, 2)));
+        assert_eq!(rule.first_visible_seat(&sl, (1, 0), (-1, 1), None), None);
+        assert_eq!(rule.first_visible_seat(&sl, (1, 0), (1, 1), Some(1)), None);
+        assert_eq!(sl[(1, 2)], SeatPosition::Occupied);
+        assert_eq!(rule.count_occupied_neighbors(&sl, (3, 0)), 1);
+        assert_eq!(
+            Day11.parse("L.\nLx\n"),
+            Err(AoCError::parse(2, 2, "Unexpected character 'x'"))
+        );
+    }
+
+    #[test]
+    fn test_neighborhoods() {
+        let room = Grid::filled(5, 5, SeatPosition::Empty);
+        let count = |config: &str, pos: Pos| {
+            let rule: SeatRule = config.parse().unwrap();
+            rule.neighbor_positions(&room, pos).len()
+        };
+        assert_eq!(count("neighbors=von-neumann", (2, 2)), 4);
+        assert_eq!(count("neighbors=von-neumann", (0, 0)), 2);
+        assert_eq!(count("neighbors=moore", (0, 0)), 3);
+        assert_eq!(count("neighbors=radius:2", (2, 2)), 24);
+        assert_eq!(count("neighbors=radius:2", (0, 0)), 8);
+        assert_eq!(count("neighbors=visible:3", (0, 0)), 3);
+
+        assert_eq!(count("topology=torus,neighbors=von-neumann", (0, 0)), 4);
+        assert_eq!(count("topology=torus,neighbors=moore", (0, 0)), 8);
+        // Wrapping around, the whole room is within two steps.
+        assert_eq!(count("topology=torus,neighbors=radius:2", (0, 0)), 24);
+        assert_eq!(count("topology=torus,neighbors=radius:3", (0, 0)), 24);
+
+        let hex: SeatRule = "topology=hex,neighbors=adjacent".parse().unwrap();
+        assert_eq!(
+            hex.neighbor_positions(&room, (2, 2)),
+            vec![(1, 2), (1, 3), (2, 1), (2, 3), (3, 1), (3, 2)]
+        );
+        assert_eq!(count("topology=hex,neighbors=von-neumann", (0, 0)), 2);
+        assert_eq!(count("topology=hex,neighbors=radius:2", (2, 2)), 18);
+
+        let row = layout("L..L.L\n");
+        let visible: SeatRule = "topology=torus,neighbors=visible".parse().unwrap();
+        assert_eq!(visible.neighbor_positions(&row, (0, 0)), vec![(3, 0), (5, 0)]);
+        assert_eq!(visible.first_visible_seat(&row, (3, 0), (1, 0), None), Some((5, 0)));
+        assert_eq!(visible.first_visible_seat(&row, (5, 0), (1, 0), None), Some((0, 0)));
+        let within: SeatRule = "neighbors=visible:2".parse().unwrap();
+        assert_eq!(within.neighbor_positions(&row, (0, 0)), vec![]);
+        assert_eq!(within.neighbor_positions(&row, (3, 0)), vec![(5, 0)]);
+        // A lone seat on a torus sees no one but itself, which doesn't count.
+        assert_eq!(visible.neighbor_positions(&layout("L..\n"), (0, 0)), vec![]);
+    }
+
+    #[test]
+    fn test_seat_rule() {
+        assert_eq!(
+            "neighbors=visible, tolerance=5".parse::<SeatRule>(),
+            Ok(SeatRule::with_tolerance(Neighbors::Visible, 5))
+        );
+        assert_eq!(
+            "rule=B0/S0123".parse::<SeatRule>(),
+            Ok(SeatRule::with_tolerance(Neighbors::Adjacent, 4))
+        );
+        assert!("neighbors=diagonal".parse::<SeatRule>().is_err());
+        assert!("neighbors=radius:x".parse::<SeatRule>().is_err());
+        assert!("topology=sphere".parse::<SeatRule>().is_err());
+        let torus: SeatRule = "topology=torus,neighbors=radius:2,rule=B3/S23".parse().unwrap();
+        assert_eq!(torus.to_string(), "topology=torus,neighbors=radius:2,rule=B3/S23");
+        assert_eq!(torus.to_string().parse(), Ok(torus));
+        assert_eq!(
+            "tolerance=3,topology=hex,neighbors=visible:4".parse::<SeatRule>().unwrap().to_string(),
+            "topology=hex,neighbors=visible:4,rule=B0/S012"
+        );
+        assert!("tolerance".parse::<SeatRule>().is_err());
+        let part2 = SeatRule::with_tolerance(Neighbors::Visible, 5);
+        assert_eq!(part2.to_string(), "neighbors=visible,rule=B0/S01234");
+        assert_eq!(part2.to_string().parse(), Ok(part2));
+
+        // With B0/S, the seats alternate between empty and occupied.
+        let restless: SeatRule = "rule=B0/S".parse().unwrap();
+        assert_eq!(step(&layout("L.L\n"), &restless), layout("#.#\n"));
+        assert_eq!(step(&layout("#.#\n"), &restless), layout("L.L\n"));
+    }
+
+    #[test]
+    fn test_simulate() {
+        let example = layout(include_str!("../../11/example1.txt"));
+        let rule_one = SeatRule::with_tolerance(Neighbors::Adjacent, 4);
+        let (outcome, settled) = simulate(&example, &rule_one, MAX_STEPS);
+        assert_eq!(outcome, Outcome::Converged { steps: 5 });
+        assert_eq!(count_occupied(&settled), 37);
+
+        let restless: SeatRule = "rule=B0/S".parse().unwrap();
+        let (outcome, _) = simulate(&example, &restless, MAX_STEPS);
+        assert_eq!(outcome, Outcome::Cycle { start: 0, length: 2 });
+        let (outcome, _) = simulate(&example, &restless, 1);
+        assert_eq!(outcome, Outcome::MaxSteps { steps: 1 });
+        let mut recorder = FrameRecorder::new();
+        simulate_observed(&example, &rule_one, MAX_STEPS, |step, layout| {
+            recorder.record(step, layout)
+        });
+        let steps: Vec<usize> = recorder.frames().iter().map(|(step, _)| *step).collect();
+        assert_eq!(steps, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(recorder.frames()[5].1, settled);
+        let mut converging = FrameRecorder::new();
+        run_to_convergence_observed(&example, &rule_one, |step, layout| {
+            converging.record(step, layout)
+        }).unwrap();
+        assert_eq!(converging.frames(), recorder.frames());
+        assert_eq!(
+            run_to_convergence(&example, &restless),
+            Err(AoCError::no_solution(
+                "The seats never settle: entered a cycle of length 2 at step 0"
+            ))
+        );
+    }
 
     #[test]
-    fn test_get_neighbor() {
-        let sl = SeatLayoutRuleOne{rows: SeatLayoutRuleOne::get_rows_from_str(
-            "LL#\n\
-             LLL\n\
-             LL.\n"
-        )};
-        assert!(sl.get(0, 2) == Some(&SeatPosition::Occupied));
-        assert!(sl.get(2, 2) == Some(&SeatPosition::Floor));
-        assert!(sl.get(0, 0) == Some(&SeatPosition::Empty));
-        assert!(sl.get(-1, 1) == None);
+    fn test_engine() {
+        let example = layout(include_str!("../../11/example1.txt"));
+        let rules = [
+            SeatRule::with_tolerance(Neighbors::Adjacent, 4),
+            SeatRule::with_tolerance(Neighbors::Visible, 5),
+            "rule=B0/S".parse().unwrap(),
+            "neighbors=visible,rule=B12/S234".parse().unwrap(),
+            "topology=torus,neighbors=von-neumann,rule=B1/S12".parse().unwrap(),
+            "topology=torus,neighbors=visible:3,tolerance=3".parse().unwrap(),
+            "topology=hex,neighbors=radius:2,rule=B2/S3456".parse().unwrap(),
+        ];
+        for layout in [example, generate_layout(40, 30, 11)] {
+            for rule in &rules {
+                let mut engine = SeatEngine::new(&layout, rule);
+                let mut expected = layout.clone();
+                for _ in 0..20 {
+                    let next = step(&expected, rule);
+                    assert_eq!(engine.step(), next != expected, "{}", rule);
+                    expected = next;
+                    assert_eq!(engine.layout(), expected, "{}", rule);
+                }
+                assert_eq!(engine.count_occupied(), count_occupied(&expected));
+            }
+        }
+
+        let example = layout(include_str!("../../11/example1.txt"));
+        for layout in [example, generate_layout(40, 30, 11)] {
+            for rule in &rules {
+                for max_steps in [1, MAX_STEPS] {
+                    let (outcome, expected) = simulate(&layout, rule, max_steps);
+                    let mut engine = SeatEngine::new(&layout, rule);
+                    assert_eq!(engine.run(max_steps), outcome, "{}", rule);
+                    assert_eq!(engine.layout(), expected, "{}", rule);
+
+                    // With every hash colliding, only real repeats are cycles.
+                    let mut engine = SeatEngine::new(&layout, rule);
+                    engine.keys.iter_mut().for_each(|key| *key = 0);
+                    engine.hash = 0;
+                    assert_eq!(engine.run(max_steps), outcome, "{}", rule);
+                    assert_eq!(engine.layout(), expected, "{}", rule);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel() {
+        let example = layout(include_str!("../../11/example1.txt"));
+        let rules: [SeatRule; 3] = [
+            SeatRule::with_tolerance(Neighbors::Adjacent, 4),
+            SeatRule::with_tolerance(Neighbors::Visible, 5),
+            "topology=hex,neighbors=radius:2,rule=B1/S3456".parse().unwrap(),
+        ];
+        for layout in [example, generate_layout(60, 45, 7), generate_layout(0, 5, 1)] {
+            for rule in &rules {
+                let mut expected = layout.clone();
+                for _ in 0..10 {
+                    let next = serial_step(&expected, rule);
+                    assert_eq!(par_step(&expected, rule), next, "{}", rule);
+                    expected = next;
+                }
+            }
+        }
     }
 
     #[test]
     fn test_step() {
+        let rule_one = SeatRule::with_tolerance(Neighbors::Adjacent, 4);
         let step0_mini = "##.\n\
                           ###";
         let step1_mini = "#L.\n\
                           #L#";
-        let sl_step0_mini = SeatLayoutRuleOne{rows: SeatLayoutRuleOne::get_rows_from_str(step0_mini)};
-        let sl_step1_mini = SeatLayoutRuleOne{rows: SeatLayoutRuleOne::get_rows_from_str(step1_mini)};
-        println!("step0_mini:\n{}", sl_step0_mini.to_string());
-        println!("step1_mini:\n{}", step(&sl_step0_mini).to_string());
-
-        assert!(step(&sl_step0_mini) == sl_step1_mini);
+        println!("step1_mini:\n{}", step(&layout(step0_mini), &rule_one));
+        assert!(step(&layout(step0_mini), &rule_one) == layout(step1_mini));
 
         let step0 = "L.LL.LL.LL\n\
                      LLLLLLL.LL\n\
@@ -310,12 +883,9 @@ mod tests {
                      #LLLLLLLL#\n\
                      #.LLLLLL.L\n\
                      #.#LLLL.##";
-        let sl_step0 = SeatLayoutRuleOne{rows: SeatLayoutRuleOne::get_rows_from_str(step0)};
-        let sl_step1 = SeatLayoutRuleOne{rows: SeatLayoutRuleOne::get_rows_from_str(step1)};
-        let sl_step2 = SeatLayoutRuleOne{rows: SeatLayoutRuleOne::get_rows_from_str(step2)};
-        println!("step1:\n{}", step(&sl_step0).to_string());
-        assert!(step(&sl_step0) == sl_step1);
-        println!("step2:\n{}", step(&sl_step1).to_string());
-        assert!(step(&sl_step1) == sl_step2);
+        println!("step1:\n{}", step(&layout(step0), &rule_one));
+        assert!(step(&layout(step0), &rule_one) == layout(step1));
+        println!("step2:\n{}", step(&layout(step1), &rule_one));
+        assert!(step(&layout(step1), &rule_one) == layout(step2));
     }
 }
@@ -1,41 +1,56 @@
 use std::vec::Vec;
+use aoc_core::{AoCError, Answer, Solution, debug, parse};
 
-pub fn run_day12(puzzle_input: &str) {
-    let nav_instrs: Vec<(String, u32)> = std::fs::read_to_string(&puzzle_input)
-        .expect(format!("Could not read file \"{}\".", puzzle_input).as_str())
-        .split('\n').filter_map(|line| {
-            if line.len() <= 1 { return None; }
-            Some((
-                String::from(&line[0..1]),
-                line[1..].parse::<u32>().expect(
-                    format!("Could not parse \"{}\"", line).as_str()
-                )
-            ))
-        }).collect();
-    let dest = follow_ship_only_instructions(&nav_instrs);
-    println!(
-        "Final position from ship instructions: ({}, {}), \
-        Manhattan distance: {}",
-        dest.0, dest.1, dest.0.abs() + dest.1.abs()
-    );
+pub struct Day12;
 
-    let (dest, _) = follow_waypoint_instructions(&nav_instrs, (10, 1));
-    println!(
-        "Final position from waypoint instructions: ({}, {}), \
-        Manhattan distance: {}",
-        dest.0, dest.1, dest.0.abs() + dest.1.abs()
-    );
+impl Solution for Day12 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 12;
+
+    type Input = Vec<(String, u32)>;
+
+    /// Only accepts known actions and rotations by multiples of 90°, so
+    /// that following the instructions cannot fail.
+    fn parse(&self, input: &str) -> Result<Vec<(String, u32)>, AoCError> {
+        parse::lines(input).map(|(i, line)| {
+            let line = line.trim_end();
+            let action = line.chars().next().unwrap();
+            if !"NSEWLRF".contains(action) {
+                return Err(AoCError::parse(
+                    i, 1, format!("Unrecognized action '{}'", action)
+                ));
+            }
+            let val_str = &line[action.len_utf8()..];
+            let val: u32 = parse::value(val_str, i, 2)?;
+            if "LR".contains(action) && !val.is_multiple_of(90) {
+                return Err(AoCError::parse(
+                    i, 2, format!("Rotating by {}° not implemented", val)
+                ));
+            }
+            Ok((action.to_string(), val))
+        }).collect()
+    }
+
+    fn part1(&self, nav_instrs: &Vec<(String, u32)>) -> Result<Answer, AoCError> {
+        let dest = follow_ship_only_instructions(nav_instrs);
+        Ok((dest.0.abs() + dest.1.abs()).into())
+    }
+
+    fn part2(&self, nav_instrs: &Vec<(String, u32)>) -> Result<Answer, AoCError> {
+        let (dest, _) = follow_waypoint_instructions(nav_instrs, (10, 1));
+        Ok((dest.0.abs() + dest.1.abs()).into())
+    }
 }
 
 fn rotate_90_cw(ori: (i64, i64), angle: i64) -> (i64, i64) {
     // In Rust, % is the remainder, not modulo:
-    let angle = ((angle % 360) + 360) % 360;
+    let angle = angle.rem_euclid(360);
     match angle {
         0 => { ori },
         90 => { (ori.1, -ori.0) },
         180 => { (-ori.0, -ori.1) },
         270 => { (-ori.1, ori.0) },
-        e => { panic!("Rotating by {}° not implemented", e); }
+        e => { unreachable!("Rotating by {}° is rejected in parse", e); }
     }
 }
 
@@ -67,7 +82,7 @@ fn follow_ship_only_instructions(
                 pos = (pos.0 + ori.0 * val,
                        pos.1 + ori.1 * val);
             },
-            e => { panic!("Unrecognized action {}", e); }
+            e => { unreachable!("Action {} is rejected in parse", e); }
         }
     }
     pos
@@ -94,9 +109,9 @@ fn follow_waypoint_instructions(
                     ship_pos.1 + val * waypoint.1
                 );
         },
-            e => { panic!("Unrecognized action{}", e); }
+            e => { unreachable!("Action {} is rejected in parse", e); }
         }
-        println!("pos and waypoint after {}{}: {:?}", action, val, (&ship_pos, &waypoint));
+        debug!("pos and waypoint after {}{}: {:?}", action, val, (&ship_pos, &waypoint));
     }
     (ship_pos, waypoint)
 }
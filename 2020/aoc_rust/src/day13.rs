@@ -1,51 +1,80 @@
 extern crate num;
 
-pub fn run_day13(puzzle_input: &str) {
-    let lines: Vec<String> = std::fs::read_to_string(&puzzle_input)
-        .expect(
-            format!("Could not read file \"{}\".", puzzle_input).as_str()
-        )
-        .split('\n')
-        .map(|l| String::from(l))
-        .collect();
-    if lines.len() < 3 {
-        panic!("Incomplete notes, only {} lines", lines.len());
+use aoc_core::{AoCError, Answer, Solution, parse};
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 13;
+
+    type Input = Notes;
+
+    fn parse(&self, input: &str) -> Result<Notes, AoCError> {
+        let lines: Vec<&str> = input
+            .split('\n')
+            .map(str::trim_end)
+            .collect();
+        if lines.len() < 2 {
+            return Err(AoCError::malformed(format!(
+                "Incomplete notes, only {} lines", lines.len()
+            )));
+        }
+        let earliest_departure: u64 = parse::value(lines[0], 1, 1)?;
+        let mut bus_ids: Vec<Option<u64>> = Vec::new();
+        let mut column = 1;
+        for s in lines[1].split(',') {
+            let bus_id = match s {
+                "x" => None,
+                _ => match parse::value::<u64>(s, 2, column)? {
+                    0 => return Err(AoCError::parse(2, column, "Bus ID 0")),
+                    id => Some(id),
+                },
+            };
+            bus_ids.push(bus_id);
+            column += s.len() + 1;
+        }
+        if bus_ids.iter().all(Option::is_none) {
+            return Err(AoCError::parse(2, 1, "No bus IDs"));
+        }
+        Ok(Notes { earliest_departure, bus_ids })
     }
-    let earliest_departure: u64 = lines[0].parse::<u64>()
-        .expect(format!(
-            "Could not parse earliest departure \"{}\"",
-            lines[0]
-        ).as_str());
-    let bus_ids: Vec<Option<u64>> = lines[1].split(',')
-        .map(|s| s.parse::<u64>().ok())
-        .collect();
-    let bus_ids_filtered: Vec<u64> = bus_ids.iter()
-        .filter_map(|id| *id).collect();
-    let earliest_bus_id = bus_ids_filtered.iter()
-        .min_by(|a, b| {
-            let wait_a: u64 = *a - &earliest_departure % *a;
-            let wait_b: u64 = *b - &earliest_departure % *b;
-            wait_a.cmp(&wait_b)
-        })
-        .unwrap();
-    let wait_time = earliest_bus_id - earliest_departure % earliest_bus_id;
-    println!(
-        "Earliest bus after {}: {}, waiting time: {}, prod: {}",
-        earliest_departure,
-        earliest_bus_id,
-        wait_time,
-        earliest_bus_id * wait_time
-    );
 
+    fn part1(&self, notes: &Notes) -> Result<Answer, AoCError> {
+        let earliest_departure = notes.earliest_departure;
+        let earliest_bus_id = notes.bus_ids.iter()
+            .filter_map(|id| *id)
+            .min_by_key(|id| id - earliest_departure % id)
+            .unwrap(); // checked in parse
+        let wait_time = earliest_bus_id - earliest_departure % earliest_bus_id;
+        Ok((earliest_bus_id * wait_time).into())
+    }
+
+    fn part2(&self, notes: &Notes) -> Result<Answer, AoCError> {
+        Ok(find_contest_timestamp(&notes.bus_ids).into())
+    }
+}
+
+pub struct Notes {
+    earliest_departure: u64,
+    /// `None` for buses that are out of service ("x").
+    bus_ids: Vec<Option<u64>>,
+}
+
+fn find_contest_timestamp(bus_ids: &[Option<u64>]) -> u64 {
     let mut contest_timestamp: u64 = 0;
     let mut max_bus_tab_id: usize = 0;
-    let mut reference_bus = bus_ids_filtered[0];
+    let mut reference_bus = bus_ids.iter()
+        .find_map(|id| *id)
+        .expect("No bus IDs, which parse rejects");
     loop {
         let mut seq_correct = true;
         for (i, bus_id) in bus_ids[1..].iter().enumerate() {
-            if let None = bus_id { continue; }
-            let bus_id = bus_id.unwrap();
-            if (contest_timestamp + i as u64 + 1) % bus_id != 0 {
+            let bus_id = match bus_id {
+                Some(bus_id) => *bus_id,
+                None => continue,
+            };
+            if !(contest_timestamp + i as u64 + 1).is_multiple_of(bus_id) {
                 seq_correct = false;
                 break;
             }
@@ -63,9 +92,8 @@ pub fn run_day13(puzzle_input: &str) {
                     });
             }
         }
-        // println!("{}", &contest_timestamp);
         if seq_correct { break; }
         contest_timestamp += reference_bus;
     }
-    println!("Contest timestamp: {}", contest_timestamp);
+    contest_timestamp
 }
@@ -3,29 +3,60 @@ extern crate regex;
 use std::vec::Vec;
 use std::collections::HashMap;
 use regex::Regex;
+use aoc_core::{AoCError, Answer, Solution, parse};
 
-pub fn run_day14(puzzle_input: &str) {
-    lazy_static! {
-        static ref MASK: Regex = Regex::new(
-            r"^mask = (?P<mask>[01X]+)$"
-        ).unwrap();
-        static ref MEM: Regex = Regex::new(
-            r"^mem\[(?P<addr>\d+)\] = (?P<val>\d+)$"
-        ).unwrap();
+lazy_static! {
+    static ref MASK: Regex = Regex::new(
+        r"^mask = (?P<mask>[01X]{36})$"
+    ).unwrap();
+    static ref MEM: Regex = Regex::new(
+        r"^mem\[(?P<addr>\d+)\] = (?P<val>\d+)$"
+    ).unwrap();
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 14;
+
+    type Input = Vec<Command>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Command>, AoCError> {
+        parse::lines(input).map(|(i, line)| {
+            let line = line.trim_end();
+            if let Some(mask_cap) = MASK.captures(line) {
+                Ok(Command::Mask(mask_cap.name("mask").unwrap().as_str().to_string()))
+            } else if let Some(mem_cap) = MEM.captures(line) {
+                let addr = mem_cap.name("addr").unwrap();
+                let val = mem_cap.name("val").unwrap();
+                Ok(Command::Mem(
+                    parse::value(addr.as_str(), i, addr.start() + 1)?,
+                    parse::value(val.as_str(), i, val.start() + 1)?,
+                ))
+            } else {
+                Err(AoCError::parse(
+                    i, 1, "Expected \"mask = <36 × 0/1/X>\" or \"mem[<addr>] = <value>\""
+                ))
+            }
+        }).collect()
+    }
+
+    fn part1(&self, code: &Vec<Command>) -> Result<Answer, AoCError> {
+        let mem = MaskV1::compute(&code[..]);
+        Ok(mem.values().sum::<u64>().into())
+    }
+
+    fn part2(&self, code: &Vec<Command>) -> Result<Answer, AoCError> {
+        let mem = MaskV2::compute(&code[..]);
+        Ok(mem.values().sum::<u64>().into())
     }
-    let code: Vec<String> = std::fs::read_to_string(&puzzle_input)
-        .expect(format!("Could not read file \"{}\".", puzzle_input).as_str())
-        .split('\n')
-        .map(|l| String::from(l))
-        .collect();
-
-    let mem = MaskV1::compute(&code[..], &MASK, &MEM);
-    let mem_sum: u64 = mem.values().sum();
-    println!("Version 1: Sum of values in memory: {}", mem_sum);
-
-    let mem = MaskV2::compute(&code[..], &MASK, &MEM);
-    let mem_sum: u64 = mem.values().sum();
-    println!("Version 2: Sum of values in memory: {}", mem_sum);
+}
+
+pub enum Command {
+    /// A bitmask of exactly 36 '0', '1' or 'X' characters.
+    Mask(String),
+    Mem(u64, u64), // addr, val
 }
 
 struct MaskV2 {
@@ -58,7 +89,7 @@ impl MaskV2 {
                     zeros_floating = new_zeros_f;
                 },
                 '0' => {},  // ignore
-                e => { panic!("Invalid bitmask char: {}", e); }
+                e => { unreachable!("Invalid bitmask char: {}", e); }
             }
         }
         MaskV2 { ones_fixed, ones_floating, zeros_floating }
@@ -85,27 +116,15 @@ impl MaskV2 {
         }
     }
 
-    fn compute(
-        code: &[String],
-        mask_pattern: &Regex,
-        mem_pattern: &Regex
-    ) -> HashMap<u64, u64> {
+    fn compute(code: &[Command]) -> HashMap<u64, u64> {
         let mut current_mask: MaskV2 = MaskV2::invariant();
         let mut mem: HashMap<u64, u64> = HashMap::new();
-        for line in code {
-            if let Some(mask_cap) = mask_pattern.captures(&line) {
-                current_mask = MaskV2::new(
-                    mask_cap.name("mask")
-                       .unwrap().as_str()
-                );
-            } else if let Some(mem_cap) = mem_pattern.captures(&line) {
-                current_mask.apply(
-                    mem_cap.name("addr").unwrap().as_str()
-                        .parse::<u64>().unwrap(),
-                    mem_cap.name("val").unwrap().as_str()
-                        .parse::<u64>().unwrap(),
-                    &mut mem
-                );
+        for command in code {
+            match command {
+                Command::Mask(mask) => { current_mask = MaskV2::new(mask); },
+                Command::Mem(addr, val) => {
+                    current_mask.apply(*addr, *val, &mut mem);
+                },
             }
         }
         mem
@@ -127,7 +146,7 @@ impl MaskV1 {
                 '0' => { zeros ^= 1 << i; },
                 '1' => { ones |= 1 << i; },
                 'X' => {},  // ignore
-                e => { panic!("Invalid bitmask char: {}", e); }
+                e => { unreachable!("Invalid bitmask char: {}", e); }
             }
         }
         MaskV1 { ones, zeros }
@@ -141,28 +160,15 @@ impl MaskV1 {
         (val & self.zeros) | self.ones
     }
 
-    fn compute(
-        code: &[String],
-        mask_pattern: &Regex,
-        mem_pattern: &Regex
-    ) -> HashMap<u64, u64> {
+    fn compute(code: &[Command]) -> HashMap<u64, u64> {
         let mut current_mask: MaskV1 = MaskV1::invariant();
         let mut mem: HashMap<u64, u64> = HashMap::new();
-        for line in code {
-            if let Some(mask_cap) = mask_pattern.captures(&line) {
-                current_mask = MaskV1::new(
-                    mask_cap.name("mask")
-                       .unwrap().as_str()
-                );
-            } else if let Some(mem_cap) = mem_pattern.captures(&line) {
-                mem.insert(
-                    mem_cap.name("addr").unwrap().as_str()
-                        .parse::<u64>().unwrap(),
-                    current_mask.apply(
-                        mem_cap.name("val").unwrap().as_str()
-                            .parse::<u64>().unwrap()
-                    )
-                );
+        for command in code {
+            match command {
+                Command::Mask(mask) => { current_mask = MaskV1::new(mask); },
+                Command::Mem(addr, val) => {
+                    mem.insert(*addr, current_mask.apply(*val));
+                },
             }
         }
         mem
@@ -1,19 +1,26 @@
 use std::vec::Vec;
 use std::collections::HashMap;
+use aoc_core::{AoCError, Answer, Solution, parse};
 
-pub fn run_day15(puzzle_input: &str) {
-    let starting_nums: Vec<u32> = std::fs::read_to_string(&puzzle_input)
-        .expect(format!("Could not read file \"{}\".", puzzle_input).as_str())
-        .trim().split(',')
-        .map(|s| s.parse::<u32>().unwrap())
-        .collect();
+pub struct Day15;
 
-    let last_num_spoken = play_game(&starting_nums, 2020);
-    println!("Num spoken in turn 2020: {}", last_num_spoken);
+impl Solution for Day15 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 15;
 
-    let last_num_spoken = play_game(&starting_nums, 30000000);
-    println!("Num spoken in turn 30000000: {}", last_num_spoken);
+    type Input = Vec<u32>;
 
+    fn parse(&self, input: &str) -> Result<Vec<u32>, AoCError> {
+        parse::comma_separated(input.trim_end(), 1)
+    }
+
+    fn part1(&self, starting_nums: &Vec<u32>) -> Result<Answer, AoCError> {
+        Ok(play_game(starting_nums, 2020).into())
+    }
+
+    fn part2(&self, starting_nums: &Vec<u32>) -> Result<Answer, AoCError> {
+        Ok(play_game(starting_nums, 30000000).into())
+    }
 }
 
 fn play_game(starting_nums: &[u32], end_turn: u32) -> u32 {
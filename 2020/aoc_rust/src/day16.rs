@@ -3,60 +3,88 @@ extern crate regex;
 use std::vec::Vec;
 use std::collections::{HashMap, BTreeSet};
 use regex::Regex;
+use aoc_core::{AoCError, Answer, Solution, debug, parse};
 
-pub fn run_day16(puzzle_input: &str) {
-    let input: String = std::fs::read_to_string(&puzzle_input)
-        .expect(format!(
-            "Could not read file \"{}\".",
-            &puzzle_input
-        ).as_str());
-    let sections: Vec<&str> = input.split("\n\n").collect();
-    if sections.len() != 3 {
-        panic!("\"{}\" is not a 3-section input file", &puzzle_input);
-    }
-    let rules: HashMap<String, Vec<(u32, u32)>> = parse_rules(&sections[0]);
-    let my_ticket: Vec<Vec<u32>> = parse_tickets(&sections[1]);
-    assert!(my_ticket.len() == 1);
-    let my_ticket: Vec<u32> = my_ticket[0].clone();
-    let nearby: Vec<Vec<u32>> = parse_tickets(&sections[2]);
+pub struct Day16;
+
+impl Solution for Day16 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 16;
 
-    let scanning_err_rate: u32 = nearby.iter().map(|t| {
-        get_invalid_values(&t, &rules).iter().sum::<u32>()
-    }).sum();
-    println!("Scanning error rate: {}", scanning_err_rate);
+    type Input = TicketNotes;
 
-    let nearby_valid: Vec<Vec<u32>> = nearby.iter().filter_map(|t| {
-        if get_invalid_values(t, &rules).len() == 0 {
-            Some(t.clone())
-        } else {
-            None
+    fn parse(&self, input: &str) -> Result<TicketNotes, AoCError> {
+        let sections: Vec<Vec<(usize, &str)>> = parse::numbered_blocks(input);
+        if sections.len() != 3 {
+            return Err(AoCError::malformed(format!(
+                "Expected 3 sections separated by empty lines, found {}",
+                sections.len()
+            )));
         }
-    }).collect();
-    // determine order of fields
-    if let Some(field_positions) = infer_field_positions(
-        &rules,
-        &nearby_valid
-    ) {
+        let rules: HashMap<String, Vec<(u32, u32)>> = parse_rules(&sections[0])?;
+        let my_ticket: Vec<Vec<u32>> = parse_tickets(&sections[1], "your ticket:")?;
+        if my_ticket.len() != 1 {
+            return Err(AoCError::parse(
+                sections[1][0].0, 1, "Expected exactly one ticket of my own"
+            ));
+        }
+        let my_ticket: Vec<u32> = my_ticket[0].clone();
+        let nearby: Vec<Vec<u32>> = parse_tickets(&sections[2], "nearby tickets:")?;
+        for (ticket, (i, _)) in nearby.iter().zip(&sections[2][1..]) {
+            if ticket.len() != my_ticket.len() {
+                return Err(AoCError::parse(*i, 1, format!(
+                    "Expected {} values like on my ticket, found {}",
+                    my_ticket.len(), ticket.len()
+                )));
+            }
+        }
+        Ok(TicketNotes { rules, my_ticket, nearby })
+    }
+
+    fn part1(&self, notes: &TicketNotes) -> Result<Answer, AoCError> {
+        let scanning_err_rate: u32 = notes.nearby.iter().map(|t| {
+            get_invalid_values(t, &notes.rules).iter().sum::<u32>()
+        }).sum();
+        Ok(scanning_err_rate.into())
+    }
+
+    fn part2(&self, notes: &TicketNotes) -> Result<Answer, AoCError> {
+        let nearby_valid: Vec<Vec<u32>> = notes.nearby.iter().filter_map(|t| {
+            if get_invalid_values(t, &notes.rules).is_empty() {
+                Some(t.clone())
+            } else {
+                None
+            }
+        }).collect();
+        // determine order of fields
+        let field_positions = infer_field_positions(
+            &notes.rules,
+            &nearby_valid
+        ).ok_or_else(|| AoCError::no_solution(
+            "Could not infer field positions, got stuck"
+        ))?;
         // multiply departure values of my_ticket
         let mut departure_vals_product: u64 = 1;
         for (field_name, pos) in &field_positions {
             if field_name.starts_with("departure") {
-                departure_vals_product *= my_ticket[*pos] as u64;
+                departure_vals_product *= notes.my_ticket[*pos] as u64;
             }
         }
-        println!("Field positions: {:?}", &field_positions);
-        println!("Product of my departure values: {}", departure_vals_product);
-    } else {
-        println!("Could not infer field positions, got stuck.");
+        Ok(departure_vals_product.into())
     }
 }
 
+pub struct TicketNotes {
+    rules: HashMap<String, Vec<(u32, u32)>>,
+    my_ticket: Vec<u32>,
+    nearby: Vec<Vec<u32>>,
+}
+
 fn infer_field_positions(
     rules: &HashMap<String, Vec<(u32, u32)>>,
     valid_tickets: &[Vec<u32>],
 ) -> Option<HashMap<String, usize>> {
-    assert!(valid_tickets.len() > 0);
-    let num_positions = valid_tickets[0].len();
+    let num_positions = valid_tickets.first()?.len();
     let mut field_position_opts: HashMap<String, BTreeSet<usize>> =
         HashMap::new();
     for (rule_name, ranges) in rules {
@@ -64,10 +92,7 @@ fn infer_field_positions(
         for pos in 0..num_positions {
             let mut pos_valid = true;
             for ticket in valid_tickets {
-                if ticket.len() < num_positions {
-                    panic!("Ticket is shorter than {} items: {:?}",
-                           num_positions, &ticket);
-                }
+                if ticket.len() < num_positions { return None; }
                 let mut matches_any_range = false;
                 for range in ranges {
                     if ticket[pos] >= range.0 && ticket[pos] <= range.1 {
@@ -91,7 +116,7 @@ fn infer_field_positions(
 
     // Resolve rules that might apply to multiple positions.
     // E.g., {'rule a': [0, 1], 'rule b': [1]} -> {'rule a': 0, 'rule b': 1}
-    println!("{:?}", &field_position_opts);
+    debug!("{:?}", &field_position_opts);
     let mut field_positions: HashMap<String, usize> = HashMap::new();
     loop {
         let mut unique_field: Option<(String, usize)> = None;
@@ -102,7 +127,7 @@ fn infer_field_positions(
                 unique_field = Some((field.clone(), *positions.first().unwrap()));
             }
         }
-        if field_positions.len() < num_positions && unique_field == None {
+        if field_positions.len() < num_positions && unique_field.is_none() {
             return None;
         }
         // Found a field with a single position -> remove this option from
@@ -112,7 +137,7 @@ fn infer_field_positions(
             unique_field.0.clone(),
             unique_field.1
         );
-        for (_, positions) in &mut field_position_opts {
+        for positions in field_position_opts.values_mut() {
             positions.remove(&unique_field.1);
         }
     }
@@ -148,44 +173,51 @@ fn valid_rule_exists(
     false
 }
 
-fn parse_tickets(tickets_str: &str) -> Vec<Vec<u32>> {
-    let lines: Vec<String> = tickets_str.trim().split('\n')
-        .map(|s| String::from(s)).collect();
-    println!("first line: {}", lines[0]);
-    assert!(lines[0] == "your ticket:" || lines[0] == "nearby tickets:");
-    let mut tickets: Vec<Vec<u32>> = Vec::new();
-    for line in &lines[1..] {
-        tickets.push(
-            line.split(',').filter_map(|s| {
-                s.parse::<u32>().ok()
-            }).collect()
-        );
+/// Parses a section of numbered lines that starts with the given header.
+fn parse_tickets(
+    lines: &[(usize, &str)],
+    header: &str
+) -> Result<Vec<Vec<u32>>, AoCError> {
+    let (header_line, first_line) = lines[0];
+    if first_line != header {
+        return Err(AoCError::parse(
+            header_line, 1, format!("Expected \"{}\"", header)
+        ));
     }
-    tickets
+    lines[1..].iter()
+        .map(|(i, line)| parse::comma_separated::<u32>(line, *i))
+        .collect()
 }
 
 fn parse_rules(
-    rules_str: &str
-) -> HashMap<String, Vec<(u32, u32)>> {
+    lines: &[(usize, &str)]
+) -> Result<HashMap<String, Vec<(u32, u32)>>, AoCError> {
     lazy_static! {
-        // (?m) enables "multiline mode":
         static ref RULE: Regex = Regex::new(
-            r"(?m)^(?P<name>[\w\s]+): (?P<ranges>.*)$"
+            r"^(?P<name>[\w\s]+): (?P<ranges>.*)$"
         ).unwrap();
         static ref RANGE: Regex = Regex::new(
             r"(?P<from>\d+)-(?P<to>\d+)"
         ).unwrap();
     }
     let mut rules: HashMap<String, Vec<(u32, u32)>> = HashMap::new();
-    for rule_cap in RULE.captures_iter(&rules_str) {
+    for (i, line) in lines {
+        let rule_cap = RULE.captures(line).ok_or_else(|| AoCError::parse(
+            *i, 1, "Expected \"<field>: <from>-<to> or <from>-<to>\""
+        ))?;
+        let ranges = rule_cap.name("ranges").unwrap();
         let mut rule: Vec<(u32, u32)> = Vec::new();
-        for range_cap in RANGE.captures_iter(
-                rule_cap.name("ranges").unwrap().as_str()) {
+        for range_cap in RANGE.captures_iter(ranges.as_str()) {
+            let from = range_cap.name("from").unwrap();
+            let to = range_cap.name("to").unwrap();
             rule.push((
-                range_cap.name("from").unwrap().as_str()
-                    .parse::<u32>().unwrap(),
-                range_cap.name("to").unwrap().as_str()
-                    .parse::<u32>().unwrap()
+                parse::value(from.as_str(), *i, ranges.start() + from.start() + 1)?,
+                parse::value(to.as_str(), *i, ranges.start() + to.start() + 1)?,
+            ));
+        }
+        if rule.is_empty() {
+            return Err(AoCError::parse(
+                *i, ranges.start() + 1, "Expected at least one range"
             ));
         }
         rules.insert(
@@ -193,5 +225,5 @@ fn parse_rules(
             rule
         );
     }
-    rules
+    Ok(rules)
 }
@@ -1,25 +1,74 @@
-pub fn run_day18(puzzle_input: &str) {
-    let input: String = String::from(
-        std::fs::read_to_string(&puzzle_input)
-            .expect(format!(
-                "Could not read file \"{}\".",
-                &puzzle_input
-            ).as_str())
-            .trim()
-    );
+use aoc_core::{AoCError, Answer, Solution, parse};
 
-    let results: Vec<i64> = input.split('\n')
-        .map(|line| compute_left_to_right(&line))
-        .collect();
-    println!("Sum of left-to-right results: {}", results.iter().sum::<i64>());
+pub struct Day18;
 
-    let results: Vec<i64> = input.split('\n')
-        .map(|line| compute_addition_precedence(&line, true))
-        .collect();
-    println!(
-        "Sum of addition-precedence results: {}",
-        results.iter().sum::<i64>()
-    );
+impl Solution for Day18 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 18;
+
+    type Input = Vec<String>;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, AoCError> {
+        parse::lines(input)
+            .map(|(i, line)| {
+                check_syntax(line).map_err(|(col, msg)| AoCError::parse(i, col, msg))?;
+                Ok(line.trim().to_string())
+            })
+            .collect()
+    }
+
+    fn part1(&self, exprs: &Vec<String>) -> Result<Answer, AoCError> {
+        Ok(exprs.iter()
+            .map(|line| compute_left_to_right(line))
+            .sum::<i64>()
+            .into())
+    }
+
+    fn part2(&self, exprs: &Vec<String>) -> Result<Answer, AoCError> {
+        Ok(exprs.iter()
+            .map(|line| compute_addition_precedence(line, true))
+            .sum::<i64>()
+            .into())
+    }
+}
+
+/// Checks that the expression only consists of numbers, '+', '*' and
+/// matching braces, so that computing it cannot fail. Returns the 1-based
+/// column and a description of the first error.
+fn check_syntax(expr: &str) -> Result<(), (usize, &'static str)> {
+    let mut depth: usize = 0;
+    let mut expect_operand = true;
+    let mut prev_digit = false;
+    let mut num_digits = 0;
+    for (i, c) in expr.trim_end().chars().enumerate() {
+        let col = i + 1;
+        match c {
+            ' ' => { prev_digit = false; num_digits = 0; continue; },
+            '0'..='9' if expect_operand || prev_digit => { expect_operand = false; },
+            '(' if expect_operand => { depth += 1; },
+            ')' if !expect_operand => {
+                if depth == 0 { return Err((col, "Unmatched ')'")); }
+                depth -= 1;
+            },
+            '+' | '*' if !expect_operand => { expect_operand = true; },
+            '0'..='9' | '(' | ')' | '+' | '*' => {
+                return Err((col, if expect_operand {
+                    "Expected a number or '('"
+                } else {
+                    "Expected '+', '*' or ')'"
+                }));
+            },
+            _ => { return Err((col, "Unexpected character")); },
+        }
+        prev_digit = c.is_ascii_digit();
+        num_digits = if prev_digit { num_digits + 1 } else { 0 };
+        if num_digits > 18 { return Err((col, "Number is too large")); }
+    }
+    if expect_operand {
+        return Err((expr.trim_end().chars().count() + 1, "Expression is incomplete"));
+    }
+    if depth > 0 { return Err((expr.trim_end().chars().count() + 1, "Unmatched '('")); }
+    Ok(())
 }
 
 fn compute_left_to_right(expr: &str) -> i64 {
@@ -30,25 +79,23 @@ fn compute_left_to_right(expr: &str) -> i64 {
     let n = expr.len();
     for (i, c) in expr.chars().rev().enumerate() {
         match c {
-            '*' => {
-                if brace_count == 0 {
+            '*'
+                if brace_count == 0 => {
                     return compute_left_to_right(&expr[..n-i-1])
                         * compute_left_to_right(&expr[n-i..]);
-                }
-            },
-            '+' => {
-                if brace_count == 0 {
+                },
+            '+'
+                if brace_count == 0 => {
                     return compute_left_to_right(&expr[..n-i-1])
                         + compute_left_to_right(&expr[n-i..]);
-                }
-            },
+                },
             ')' => {
                 brace_count += 1;
-                if brace_start == None { brace_start = Some(i); }
+                if brace_start.is_none() { brace_start = Some(i); }
             },
             '(' => {
                 if brace_count == 0 {
-                    panic!("Mismatched braces in \"{}\"", &expr);
+                    unreachable!("Mismatched braces in \"{}\"", &expr);
                 }
                 brace_count -= 1;
                 brace_end = i;
@@ -62,9 +109,8 @@ fn compute_left_to_right(expr: &str) -> i64 {
     if let Some(brace_start) = brace_start {
         return compute_left_to_right(&expr[n-brace_end..n-brace_start-1]);
     }
-    expr.trim().parse::<i64>().expect(
-        format!("\"{}\" is not a number", &expr).as_str()
-    )
+    expr.trim().parse::<i64>()
+        .unwrap_or_else(|_| unreachable!("\"{}\" is not a number", &expr))
 }
 
 fn compute_addition_precedence(expr: &str, ignore_sums: bool) -> i64 {
@@ -78,12 +124,11 @@ fn compute_addition_precedence(expr: &str, ignore_sums: bool) -> i64 {
     let mut addition_count: usize = 0;
     for (i, c) in expr.chars().rev().enumerate() {
         match c {
-            '*' => {
-                if brace_count == 0 {
+            '*'
+                if brace_count == 0 => {
                     return compute_addition_precedence(&expr[..n-i-1], true)
                         * compute_addition_precedence(&expr[n-i..], true);
-                }
-            },
+                },
             '+' => {
                 if brace_count != 0 { continue; }
                 if !ignore_sums {
@@ -94,11 +139,11 @@ fn compute_addition_precedence(expr: &str, ignore_sums: bool) -> i64 {
             },
             ')' => {
                 brace_count += 1;
-                if brace_start == None { brace_start = Some(i); }
+                if brace_start.is_none() { brace_start = Some(i); }
             },
             '(' => {
                 if brace_count == 0 {
-                    panic!("Mismatched braces in \"{}\"", &expr);
+                    unreachable!("Mismatched braces in \"{}\"", &expr);
                 }
                 brace_count -= 1;
                 brace_end = i;
@@ -110,7 +155,7 @@ fn compute_addition_precedence(expr: &str, ignore_sums: bool) -> i64 {
         // All products outside of braces have been handled, and
         // there are some additions left that should be handled before
         // recursing into braces:
-        return compute_addition_precedence(&expr, false);
+        return compute_addition_precedence(expr, false);
     }
 
     // Found no addition or multiplication outside of braces.
@@ -120,7 +165,6 @@ fn compute_addition_precedence(expr: &str, ignore_sums: bool) -> i64 {
             &expr[n-brace_end..n-brace_start-1], true
         );
     }
-    expr.trim().parse::<i64>().expect(
-        format!("\"{}\" is not a number", &expr).as_str()
-    )
+    expr.trim().parse::<i64>()
+        .unwrap_or_else(|_| unreachable!("\"{}\" is not a number", &expr))
 }
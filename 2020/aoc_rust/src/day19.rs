@@ -2,28 +2,45 @@ extern crate regex;
 
 use std::collections::HashMap;
 use regex::Regex;
+use aoc_core::{AoCError, Answer, Solution, parse};
 
-pub fn run_day19(puzzle_input: &str) {
-    let input: Vec<String> = std::fs::read_to_string(&puzzle_input)
-        .expect(format!(
-            "Could not read file \"{}\".",
-            &puzzle_input
-        ).as_str()).trim()
-        .split("\n\n")
-        .map(|s| String::from(s))
-        .collect();
-    if input.len() != 2 {
-        println!("Input file is not separated into rules and messages.");
-        std::process::exit(1);
+pub struct Day19;
+
+impl Solution for Day19 {
+    const YEAR: u16 = 2020;
+    const DAY: u8 = 19;
+
+    type Input = (RuleSet, Vec<String>);
+
+    fn parse(&self, input: &str) -> Result<(RuleSet, Vec<String>), AoCError> {
+        let lines: Vec<(usize, &str)> = input.trim_end().split('\n')
+            .map(str::trim_end)
+            .enumerate()
+            .map(|(i, line)| (i + 1, line))
+            .collect();
+        let separator = lines.iter().position(|(_, line)| line.is_empty())
+            .ok_or_else(|| AoCError::malformed(
+                "Input is not separated into rules and messages by an empty line"
+            ))?;
+        let mut messages: Vec<String> = Vec::new();
+        for &(i, line) in &lines[separator + 1..] {
+            if let Some(col) = line.chars().position(|c| !c.is_ascii()) {
+                return Err(AoCError::parse(i, col + 1, "Messages must be ASCII"));
+            }
+            messages.push(line.to_string());
+        }
+        Ok((RuleSet::new(&lines[..separator])?, messages))
+    }
+
+    fn part1(
+        &self,
+        (rule_set, messages): &(RuleSet, Vec<String>)
+    ) -> Result<Answer, AoCError> {
+        Ok(messages.iter()
+            .filter(|m| rule_set.matches(m))
+            .count()
+            .into())
     }
-    let rules: String = input[0].clone();
-    let messages: Vec<String> = input[1].split('\n')
-        .map(|s| String::from(s)).collect();
-    let rule_set = RuleSet::new(&rules);
-    let match_count = messages.iter()
-        .map(|m| if rule_set.matches(m) {1} else {0})
-        .sum::<usize>();
-    println!("Number of matches: {}", match_count);
 }
 
 enum SubRuleItem {
@@ -31,7 +48,7 @@ enum SubRuleItem {
     Reference(usize)
 }
 
-struct RuleSet {
+pub struct RuleSet {
     /// Each rule consists of a vec of sub-rules, at least one of which
     /// must match.
     /// Each sub-rule consists of a sequence of either literal
@@ -40,42 +57,69 @@ struct RuleSet {
 }
 
 impl RuleSet {
-    fn new(str_rules: &str) -> RuleSet {
+    /// Parses the numbered lines of the rules section. Every referenced
+    /// rule, including rule 0, must exist.
+    fn new(lines: &[(usize, &str)]) -> Result<RuleSet, AoCError> {
         lazy_static! {
-            // (?m) enables "multiline mode":
             static ref RULE: Regex = Regex::new(
-                r"(?m)^(?P<rule_id>\d+): (?P<subrules>.*)$"
+                r"^(?P<rule_id>\d+): (?P<subrules>.*)$"
+            ).unwrap();
+            static ref ITEM: Regex = Regex::new(
+                r#"^(?:"(?P<literal>.)"|(?P<reference>\d+))$"#
             ).unwrap();
         }
         let mut rules: HashMap<usize, Vec<Vec<SubRuleItem>>> = HashMap::new();
-        for rule_cap in RULE.captures_iter(&str_rules) {
-            let rule_id: usize = rule_cap.name("rule_id").unwrap().as_str()
-                .parse::<usize>().unwrap();
+        let mut references: Vec<(usize, usize, usize)> = Vec::new(); // id, line, col
+        for &(i, line) in lines {
+            let rule_cap = RULE.captures(line).ok_or_else(|| AoCError::parse(
+                i, 1, "Expected \"<id>: <sub-rules>\""
+            ))?;
+            let rule_id: usize = parse::value(
+                rule_cap.name("rule_id").unwrap().as_str(), i, 1
+            )?;
+            let subrules = rule_cap.name("subrules").unwrap();
             let mut sub_rules: Vec<Vec<SubRuleItem>> = Vec::new();
-            for sub_rule_str in rule_cap.name("subrules").unwrap().as_str()
-                    .split(" | ") {
+            let mut column = subrules.start() + 1;
+            for sub_rule_str in subrules.as_str().split(" | ") {
                 let mut sub_rule: Vec<SubRuleItem> = Vec::new();
-                for item_str in sub_rule_str.trim().split(' ') {
-                    let item_str = item_str.trim();
-                    if item_str.starts_with('"') {
+                for item_str in sub_rule_str.split(' ') {
+                    let item_cap = ITEM.captures(item_str).ok_or_else(|| {
+                        AoCError::parse(i, column, format!(
+                            "Expected a rule ID or a quoted character, found \"{}\"",
+                            item_str
+                        ))
+                    })?;
+                    if let Some(literal) = item_cap.name("literal") {
                         sub_rule.push(SubRuleItem::Literal(
-                            item_str.chars().nth(1).unwrap()
+                            literal.as_str().chars().next().unwrap()
                         ));
                     } else {
-                        sub_rule.push(SubRuleItem::Reference(
-                            item_str.parse::<usize>().unwrap()
-                        ));
+                        let reference: usize = parse::value(item_str, i, column)?;
+                        references.push((reference, i, column));
+                        sub_rule.push(SubRuleItem::Reference(reference));
                     }
+                    column += item_str.chars().count() + 1;
                 }
+                column += 2; // "| "
                 sub_rules.push(sub_rule);
             }
             rules.insert(rule_id, sub_rules);
         }
-        RuleSet{ rules }
+        if !rules.contains_key(&0) {
+            return Err(AoCError::malformed("There is no rule 0"));
+        }
+        for (reference, i, column) in references {
+            if !rules.contains_key(&reference) {
+                return Err(AoCError::parse(
+                    i, column, format!("No rule found for id {}", reference)
+                ));
+            }
+        }
+        Ok(RuleSet{ rules })
     }
 
     fn matches(&self, message: &str) -> bool {
-        self.match_len(&message, 0) == message.len()
+        self.match_len(message, 0) == message.len()
     }
 
     /// Returns the number of matched characters for the given rule.
@@ -123,7 +167,7 @@ impl RuleSet {
                 0
             },
             None => {
-                panic!("No rule found for id {}", rule_id);
+                unreachable!("No rule found for id {}", rule_id);
             }
         }
     }
@@ -1,61 +1,55 @@
-pub fn run_day02(puzzle_input: &str) {
-    let input: String = String::from(
-        std::fs::read_to_string(&puzzle_input)
-            .expect(format!(
-                "Could not read file \"{}\".",
-                &puzzle_input
-            ).as_str())
-            .trim()
-    );
+use aoc_core::{AoCError, Answer, Solution, parse};
 
-    let mut depth: i64 = 0;
-    let mut pos_x: i64 = 0;
-    for line in input.split('\n') {
-        let cmd = line.split_whitespace()
-            .next()
-            .unwrap_or("");
-        let val: i64 = line.split_whitespace()
-            .nth(1)
-            .unwrap_or("")
-            .parse().unwrap();
-        // println!("cmd={}, val={}", &cmd, &val);
-        match cmd {
-            "forward" => { pos_x += val; },
-            "down" => { depth += val; },
-            "up" => { depth -= val; },
-            _ => { panic!("Command {} unknown", &cmd); }
-        }
+pub struct Day02;
+
+impl Solution for Day02 {
+    const YEAR: u16 = 2021;
+    const DAY: u8 = 2;
+
+    type Input = Vec<(String, i64)>;
+
+    /// Only accepts the commands "forward", "down" and "up".
+    fn parse(&self, input: &str) -> Result<Vec<(String, i64)>, AoCError> {
+        parse::lines(input).map(|(i, line)| {
+            let (cmd, val) = line.trim().split_once(' ').ok_or_else(|| {
+                AoCError::parse(i, 1, "Expected \"<command> <value>\"")
+            })?;
+            if !["forward", "down", "up"].contains(&cmd) {
+                return Err(AoCError::parse(
+                    i, 1, format!("Command {} unknown", cmd)
+                ));
+            }
+            let val: i64 = parse::value(val, i, cmd.len() + 2)?;
+            Ok((cmd.to_string(), val))
+        }).collect()
     }
-    println!(
-        "Part I: depth = {}, horizontal position = {}, product = {}",
-        depth,
-        pos_x,
-        depth * pos_x
-    );
 
-    let mut aim: i64 = 0;
-    let mut depth: i64 = 0;
-    let mut pos_x: i64 = 0;
-    for line in input.split('\n') {
-        let cmd = line.split_whitespace()
-            .next()
-            .unwrap_or("");
-        let val: i64 = line.split_whitespace()
-            .nth(1)
-            .unwrap_or("")
-            .parse().unwrap();
-        match cmd {
-            "forward" => { pos_x += val; depth += aim * val; },
-            "down" => { aim += val; },
-            "up" => { aim -= val; },
-            _ => { panic!("Command {} unknown", &cmd); }
+    fn part1(&self, commands: &Vec<(String, i64)>) -> Result<Answer, AoCError> {
+        let mut depth: i64 = 0;
+        let mut pos_x: i64 = 0;
+        for (cmd, val) in commands {
+            match cmd.as_str() {
+                "forward" => { pos_x += val; },
+                "down" => { depth += val; },
+                "up" => { depth -= val; },
+                _ => { unreachable!("Command {} is rejected in parse", &cmd); }
+            }
         }
+        Ok((depth * pos_x).into())
     }
-    println!(
-        "Parth II: depth = {}, horizontal position = {}, product = {}",
-        depth,
-        pos_x,
-        depth * pos_x
-    );
 
+    fn part2(&self, commands: &Vec<(String, i64)>) -> Result<Answer, AoCError> {
+        let mut aim: i64 = 0;
+        let mut depth: i64 = 0;
+        let mut pos_x: i64 = 0;
+        for (cmd, val) in commands {
+            match cmd.as_str() {
+                "forward" => { pos_x += val; depth += aim * val; },
+                "down" => { aim += val; },
+                "up" => { aim -= val; },
+                _ => { unreachable!("Command {} is rejected in parse", &cmd); }
+            }
+        }
+        Ok((depth * pos_x).into())
+    }
 }
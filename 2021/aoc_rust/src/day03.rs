@@ -1,99 +1,133 @@
-use std::path::Path;
+use aoc_core::{AoCError, Answer, Solution, debug, parse};
 
-pub fn run_day03<P: AsRef<Path>>(puzzle_input: P) {
-    let input: String = String::from(
-        std::fs::read_to_string(&puzzle_input)
-            .expect(format!(
-                "Could not read file \"{}\".",
-                puzzle_input.as_ref().display()
-            ).as_str())
-            .trim()
-    );
+pub struct Day03;
+
+impl Solution for Day03 {
+    const YEAR: u16 = 2021;
+    const DAY: u8 = 3;
 
-    fn get_most_and_least_frequent_bits(input: &str, prefix: &str) -> (String, String, usize, String) {
-        let mut freqs_zeros: Vec<u64> = Vec::new();
-        let mut freqs_ones: Vec<u64> = Vec::new();
-        let mut count: usize = 0;
-        let mut most_recent_match: &str = "";
-        for line in input.split('\n') {
-            if !line.starts_with(prefix) { continue; }
-            count += 1;
-            most_recent_match = line;
-            for (i, c) in line.trim().chars().enumerate() {
-                if freqs_zeros.len() < i + 1 { freqs_zeros.push(0); }
-                if freqs_ones.len() < i + 1 { freqs_ones.push(0); }
-                match c {
-                    '0' => { freqs_zeros[i] += 1; },
-                    '1' => { freqs_ones[i] += 1; },
-                    _ => { panic!{"Unrecognized character '{}'", c}; }
-                }
+    type Input = String;
+
+    /// Only accepts binary numbers of the same length with at most 32 bits.
+    fn parse(&self, input: &str) -> Result<String, AoCError> {
+        let mut lines: Vec<&str> = Vec::new();
+        for (i, line) in parse::lines(input) {
+            let line = line.trim();
+            if let Some(col) = line.chars().position(|c| c != '0' && c != '1') {
+                return Err(AoCError::parse(i, col + 1, "Expected '0' or '1'"));
             }
-        }
-        let mut most_frequent_bits: String = String::new();
-        let mut least_frequent_bits: String = String::new();
-        for (freq_zeros, freq_ones) in freqs_zeros.iter().zip(&freqs_ones) {
-            if freq_zeros > freq_ones {
-                most_frequent_bits += "0";
-                least_frequent_bits += "1";
-            } else if freq_zeros < freq_ones {
-                most_frequent_bits += "1";
-                least_frequent_bits += "0";
-            } else {
-                // In case of equal frequencies (relevant for part II)
-                most_frequent_bits += "1";
-                least_frequent_bits += "0";
+            if line.len() > 32 {
+                return Err(AoCError::parse(i, 33, "Numbers must have at most 32 bits"));
             }
+            if lines.first().is_some_and(|first| first.len() != line.len()) {
+                return Err(AoCError::parse(i, 1, format!(
+                    "Expected {} bits like in the first line", lines[0].len()
+                )));
+            }
+            lines.push(line);
+        }
+        if lines.is_empty() {
+            return Err(AoCError::malformed("No diagnostic report"));
         }
-        (most_frequent_bits, least_frequent_bits, count, most_recent_match.to_string())
+        Ok(lines.join("\n"))
+    }
+
+    fn part1(&self, input: &String) -> Result<Answer, AoCError> {
+        let (gamma_rate_bin, epsilon_rate_bin, _, _) = get_most_and_least_frequent_bits(input, "");
+        let gamma_rate = usize::from_str_radix(&gamma_rate_bin, 2).unwrap();
+        let epsilon_rate = usize::from_str_radix(&epsilon_rate_bin, 2).unwrap();
+        Ok((gamma_rate * epsilon_rate).into())
     }
 
-    let (gamma_rate_bin, epsilon_rate_bin, _, _) = get_most_and_least_frequent_bits(&input, "");
-    println!("gamma: {}, epsilon: {}", &gamma_rate_bin, &epsilon_rate_bin);
-    let gamma_rate = usize::from_str_radix(&gamma_rate_bin, 2).unwrap();
-    let epsilon_rate = usize::from_str_radix(&epsilon_rate_bin, 2).unwrap();
-    println!("gamma: {}, epsilon: {}, product: {}", &gamma_rate, &epsilon_rate, gamma_rate * epsilon_rate);
+    fn part2(&self, input: &String) -> Result<Answer, AoCError> {
+        let o2_generator_rating = get_o2_rating(input, 0, "")?;
+        let co2_scrubber_rating = get_co2_rating(input, 0, "")?;
+        Ok((o2_generator_rating * co2_scrubber_rating).into())
+    }
+}
 
-    fn get_o2_rating(input: &str, bit_index: usize, most_freq: &str) -> usize {
-        let (most_freq_new, _, count, most_recent_match) = get_most_and_least_frequent_bits(
-            &input,
-            &most_freq[0..bit_index]
-        );
-        println!("o2 bit index {}, most_freq {}, count {}", &bit_index, &most_freq, count);
-        if count == 1 {
-            return usize::from_str_radix(&most_recent_match, 2).unwrap();
-        } else if count < 1 {
-            panic!("No matches for o2 rating, bit index {}, most_freq {}", bit_index, most_freq);
+fn get_most_and_least_frequent_bits(input: &str, prefix: &str) -> (String, String, usize, String) {
+    let mut freqs_zeros: Vec<u64> = Vec::new();
+    let mut freqs_ones: Vec<u64> = Vec::new();
+    let mut count: usize = 0;
+    let mut most_recent_match: &str = "";
+    for line in input.split('\n') {
+        if !line.starts_with(prefix) { continue; }
+        count += 1;
+        most_recent_match = line;
+        for (i, c) in line.trim().chars().enumerate() {
+            if freqs_zeros.len() < i + 1 { freqs_zeros.push(0); }
+            if freqs_ones.len() < i + 1 { freqs_ones.push(0); }
+            match c {
+                '0' => { freqs_zeros[i] += 1; },
+                '1' => { freqs_ones[i] += 1; },
+                _ => { unreachable!("Unrecognized character '{}'", c); }
+            }
         }
-        get_o2_rating(
-            &input,
-            bit_index+1,
-            &(most_freq[..bit_index].to_string() + &most_freq_new[bit_index..])
-        )
     }
-    fn get_co2_rating(input: &str, bit_index: usize, least_freq: &str) -> usize {
-        let (_, least_freq_new, count, most_recent_match) = get_most_and_least_frequent_bits(
-            &input,
-            &least_freq[0..bit_index]
-        );
-        println!("co2 bit index {}, least_freq {}, count {}", &bit_index, &least_freq, count);
-        if count == 1 {
-            return usize::from_str_radix(&most_recent_match, 2).unwrap();
-        } else if count < 1 {
-            panic!("No matches for co2 rating, bit index {}, least_freq {}", bit_index, least_freq);
+    let mut most_frequent_bits: String = String::new();
+    let mut least_frequent_bits: String = String::new();
+    for (freq_zeros, freq_ones) in freqs_zeros.iter().zip(&freqs_ones) {
+        if freq_zeros > freq_ones {
+            most_frequent_bits += "0";
+            least_frequent_bits += "1";
+        } else if freq_zeros < freq_ones {
+            most_frequent_bits += "1";
+            least_frequent_bits += "0";
+        } else {
+            // In case of equal frequencies (relevant for part II)
+            most_frequent_bits += "1";
+            least_frequent_bits += "0";
         }
-        get_co2_rating(
-            &input,
-            bit_index+1,
-            &(least_freq[..bit_index].to_string() + &least_freq_new[bit_index..])
-        )
     }
+    (most_frequent_bits, least_frequent_bits, count, most_recent_match.to_string())
+}
 
-    let o2_generator_rating = get_o2_rating(&input, 0, "");
-    let co2_scrubber_rating = get_co2_rating(&input, 0, "");
-    println!(
-        "o2 generator rating: {}, co2 scrubber rating: {}, product: {}",
-        o2_generator_rating,
-        co2_scrubber_rating,
-        o2_generator_rating * co2_scrubber_rating
+fn get_o2_rating(
+    input: &str,
+    bit_index: usize,
+    most_freq: &str
+) -> Result<usize, AoCError> {
+    let (most_freq_new, _, count, most_recent_match) = get_most_and_least_frequent_bits(
+        input,
+        &most_freq[0..bit_index]
     );
+    debug!("o2 bit index {}, most_freq {}, count {}", &bit_index, &most_freq, count);
+    if count == 1 {
+        return Ok(usize::from_str_radix(&most_recent_match, 2).unwrap());
+    } else if count < 1 || bit_index == most_freq_new.len() {
+        return Err(AoCError::no_solution(format!(
+            "No unique match for o2 rating, bit index {}, most_freq {}",
+            bit_index, most_freq
+        )));
+    }
+    get_o2_rating(
+        input,
+        bit_index+1,
+        &(most_freq[..bit_index].to_string() + &most_freq_new[bit_index..])
+    )
+}
+fn get_co2_rating(
+    input: &str,
+    bit_index: usize,
+    least_freq: &str
+) -> Result<usize, AoCError> {
+    let (_, least_freq_new, count, most_recent_match) = get_most_and_least_frequent_bits(
+        input,
+        &least_freq[0..bit_index]
+    );
+    debug!("co2 bit index {}, least_freq {}, count {}", &bit_index, &least_freq, count);
+    if count == 1 {
+        return Ok(usize::from_str_radix(&most_recent_match, 2).unwrap());
+    } else if count < 1 || bit_index == least_freq_new.len() {
+        return Err(AoCError::no_solution(format!(
+            "No unique match for co2 rating, bit index {}, least_freq {}",
+            bit_index, least_freq
+        )));
+    }
+    get_co2_rating(
+        input,
+        bit_index+1,
+        &(least_freq[..bit_index].to_string() + &least_freq_new[bit_index..])
+    )
 }
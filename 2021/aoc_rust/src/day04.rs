@@ -1,51 +1,47 @@
-use std::error::Error;
 use std::collections::HashSet;
+use aoc_core::{AoCError, Answer, Grid, Solution, parse};
 
-pub fn run_day04(puzzle_input: &str) {
-    let input: String = String::from(
-        std::fs::read_to_string(&puzzle_input)
-            .expect(format!(
-                "Could not read file \"{}\".",
-                &puzzle_input
-            ).as_str())
-            .trim()
-    );
-    match BingoGame::new(&input) {
-        Ok(game) => {
-            let (earliest_winning_round, earliest_board_id, earliest_score) = game
-                .find_earliest_winning_board();
-            println!("earliest round: {}, earliest board: {}, score: {}",
-                     earliest_winning_round, earliest_board_id.unwrap(), earliest_score.unwrap());
+pub struct Day04;
 
-            let (latest_winning_round, latest_board_id, latest_score) = game
-                .find_latest_winning_board();
-            println!("latest round: {}, latest board: {}, score: {}",
-                     latest_winning_round, latest_board_id.unwrap(), latest_score.unwrap());
+impl Solution for Day04 {
+    const YEAR: u16 = 2021;
+    const DAY: u8 = 4;
 
-        },
-        Err(e) => {
-            panic!("{:?}", e);
-        }
+    type Input = BingoGame;
+
+    fn parse(&self, input: &str) -> Result<BingoGame, AoCError> {
+        BingoGame::new(input)
+    }
+
+    fn part1(&self, game: &BingoGame) -> Result<Answer, AoCError> {
+        let (_, _, earliest_score) = game.find_earliest_winning_board();
+        Ok(earliest_score.ok_or_else(|| AoCError::no_solution("No board wins"))?.into())
+    }
+
+    fn part2(&self, game: &BingoGame) -> Result<Answer, AoCError> {
+        let (_, _, latest_score) = game.find_latest_winning_board();
+        Ok(latest_score.ok_or_else(|| AoCError::no_solution("No board wins"))?.into())
     }
 }
 
-struct BingoGame {
+pub struct BingoGame {
     boards: Vec<BingoBoard>,
     drawn_numbers: Vec<usize>
 }
 
 impl BingoGame {
-    fn new(input: &str) -> Result<BingoGame, Box<dyn Error>> {
-        let drawn_numbers = input.split("\n").next()
-            .ok_or(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "No first line to parse the drawn numbers from."
-            ))?
-            .split(",")
-            .map(|s| s.parse::<usize>())
-            .collect::<Result<_, _>>()?;
-        let boards: Vec<BingoBoard> = input.split("\n\n")
-            .skip(1) // We already parsed the first line.
+    fn new(input: &str) -> Result<BingoGame, AoCError> {
+        let blocks = parse::numbered_blocks(input);
+        let drawn_numbers = match blocks.first() {
+            Some(block) if block[0].0 == 1 && block.len() == 1 => {
+                parse::comma_separated(block[0].1, 1)?
+            },
+            _ => return Err(AoCError::malformed(
+                "Expected the drawn numbers in the first line, followed by an empty line."
+            )),
+        };
+        // The boards are separated by empty lines:
+        let boards: Vec<BingoBoard> = blocks[1..].iter()
             .map(|block| BingoBoard::new(block))
             .collect::<Result<_, _>>()?;
         Ok(BingoGame {
@@ -86,85 +82,49 @@ impl BingoGame {
 }
 
 struct BingoBoard {
-    fields: Vec<usize>,
-    width: usize,
-    height: usize,
+    fields: Grid<usize>,
 }
 
 impl BingoBoard {
-    fn new(input_block: &str) -> Result<BingoBoard, Box<dyn Error>> {
-        let width = input_block.lines().next()
-            .ok_or(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Invalid input for BingoBoard:\n{}", input_block)
-            ))?
-            .split_ascii_whitespace()
-            .count();
-        let mut height: usize = 0;
+    /// Parses a non-empty block of numbered lines.
+    fn new(lines: &[(usize, &str)]) -> Result<BingoBoard, AoCError> {
+        let width = lines[0].1.split_ascii_whitespace().count();
         let mut fields: Vec<usize> = Vec::new();
-        for line in input_block.lines() {
-            height += 1;
-            let mut line_vals: Vec<usize> = line.split_ascii_whitespace()
-                .map(|s| s.parse::<usize>())
-                .collect::<Result<_, _>>()?;
-            let row_len: usize = line_vals.len();
-            fields.append(&mut line_vals);
+        for &(i, line) in lines {
+            let mut row_len: usize = 0;
+            let mut column = 1;
+            for s in line.split(' ') {
+                if !s.is_empty() {
+                    fields.push(parse::value(s, i, column)?);
+                    row_len += 1;
+                }
+                column += s.len() + 1;
+            }
             if row_len != width {
-                return Err(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!(
-                        "Malformed BingoBoard. Expected {} per row, but found {} in this line: {}\n\n:\n{}",
-                        width,
-                        row_len,
-                        line,
-                        input_block
-                    )
+                return Err(AoCError::parse(i, 1, format!(
+                    "Malformed BingoBoard. Expected {} numbers per row, but found {}",
+                    width, row_len
                 )));
             }
         }
         Ok(BingoBoard {
-            fields,
-            width,
-            height
+            fields: Grid::new(width, lines.len(), fields),
         })
     }
 
-    fn get(&self, x: usize, y: usize) -> &usize {
-        self.fields.get(y * self.width + x).unwrap()
-    }
-
     fn get_score(&self, drawn_numbers_so_far: &[usize]) -> usize {
         let last_called_number: &usize = drawn_numbers_so_far.last().unwrap();
         let mut drawn_numbers: HashSet<usize> = HashSet::new();
         drawn_numbers.extend(drawn_numbers_so_far);
-        let mut score: usize = 0;
-        let mut has_won: bool = false;
-        // Check rows:
-        for row_id in 0..self.height {
-            let mut num_matches: usize = 0;
-            for col_id in 0..self.width {
-                let val = self.get(col_id, row_id);
-                if drawn_numbers.contains(val) {
-                    num_matches += 1;
-                } else {
-                    score += val;
-                }
-            }
-            if num_matches == self.width {
-                has_won = true;
-            }
-        }
-        // Check cols and don't compute the score a second time:
-        for col_id in 0..self.width {
-            let mut num_matches: usize = 0;
-            for row_id in 0..self.height {
-                if drawn_numbers.contains(self.get(col_id, row_id)) {
-                    num_matches += 1;
-                }
-            }
-            if num_matches == self.height { has_won = true; }
-        }
-        if has_won { score * last_called_number } else { 0 }
+        let has_won = self.fields.rows()
+            .any(|row| row.iter().all(|val| drawn_numbers.contains(val)))
+            || self.fields.columns()
+            .any(|mut column| column.all(|val| drawn_numbers.contains(val)));
+        if !has_won { return 0; }
+        let score: usize = self.fields.iter()
+            .filter(|val| !drawn_numbers.contains(val))
+            .sum();
+        score * last_called_number
     }
 
     fn rounds_to_victory(&self, drawn_numbers: &[usize]) -> (usize, usize) {
@@ -1,26 +1,38 @@
 extern crate regex;
 
 use regex::Regex;
+use aoc_core::{AoCError, Answer, Grid, Solution, parse};
 
-pub fn run_day05(puzzle_input: &str) {
-    let input: String = String::from(
-        std::fs::read_to_string(&puzzle_input)
-            .expect(format!(
-                "Could not read file \"{}\".",
-                &puzzle_input
-            ).as_str())
-            .trim()
-    );
-    let mut field = Field::new(&input);
-    let num_intersections_hor_or_vert = field.evaluate_intersections(true);
-    println!("Number of intersections of horizontal or vertical lines: {}", num_intersections_hor_or_vert);
+pub struct Day05;
 
-    let mut field = Field::new(&input);
-    let num_intersections = field.evaluate_intersections(false);
-    println!("Number of intersections total: {}", num_intersections);
+impl Solution for Day05 {
+    const YEAR: u16 = 2021;
+    const DAY: u8 = 5;
 
+    type Input = Vec<Line>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Line>, AoCError> {
+        let lines = parse::lines(input)
+            .map(|(i, text_line)| Line::new(text_line.trim(), i))
+            .collect::<Result<Vec<Line>, AoCError>>()?;
+        if lines.is_empty() {
+            return Err(AoCError::malformed("No lines of vents"));
+        }
+        Ok(lines)
+    }
+
+    fn part1(&self, lines: &Vec<Line>) -> Result<Answer, AoCError> {
+        Ok(count_intersections(lines, true).into())
+    }
+
+    fn part2(&self, lines: &Vec<Line>) -> Result<Answer, AoCError> {
+        Ok(count_intersections(lines, false).into())
+    }
 }
 
+/// Keeps the field that is allocated for the vents at a sane size.
+const MAX_COORDINATE: usize = 9999;
+
 #[derive(Debug, Copy, Clone)]
 struct Point {
     x: usize,
@@ -28,36 +40,45 @@ struct Point {
 }
 
 #[derive(Debug, Copy, Clone)]
-struct Line {
+pub struct Line {
     start: Point,
     end: Point
 }
 
 impl Line {
-    fn new(s: &str) -> Line {
+    /// Parses a line of vents found in the given line of the input. Only
+    /// horizontal, vertical and diagonal lines at 45° are accepted.
+    fn new(s: &str, line_number: usize) -> Result<Line, AoCError> {
         lazy_static! {
             static ref RE: Regex = Regex::new(
-                r"(\d+),(\d+) -> (\d+),(\d+)"
+                r"^(\d+),(\d+) -> (\d+),(\d+)$"
             ).unwrap();
         }
-        let caps = RE.captures(s).expect(
-            &format!("No line found in '{}'", s)
-        );
-        Line {
-            // caps[0] is the full string
-            start: Point {
-                x: caps[1].parse::<usize>()
-                    .expect(format!("Invalid number in captures: {:?}", caps).as_str()),
-                y: caps[2].parse::<usize>()
-                    .expect(format!("Invalid number in captures: {:?}", caps).as_str()),
-            },
-            end: Point {
-                x: caps[3].parse::<usize>()
-                    .expect(format!("Invalid number in captures: {:?}", caps).as_str()),
-                y: caps[4].parse::<usize>()
-                    .expect(format!("Invalid number in captures: {:?}", caps).as_str()),
+        let caps = RE.captures(s).ok_or_else(|| AoCError::parse(
+            line_number, 1, "Expected \"<x1>,<y1> -> <x2>,<y2>\""
+        ))?;
+        // caps[0] is the full string
+        let mut coordinates = [0; 4];
+        for (coordinate, cap) in coordinates.iter_mut().zip(caps.iter().skip(1)) {
+            let cap = cap.unwrap();
+            *coordinate = parse::value(cap.as_str(), line_number, cap.start() + 1)?;
+            if *coordinate > MAX_COORDINATE {
+                return Err(AoCError::parse(line_number, cap.start() + 1, format!(
+                    "Coordinates must not exceed {}", MAX_COORDINATE
+                )));
             }
         }
+        let [x1, y1, x2, y2] = coordinates;
+        let line = Line {
+            start: Point { x: x1, y: y1 },
+            end: Point { x: x2, y: y2 },
+        };
+        if !line.is_horizontal_or_vertical() && x1.abs_diff(x2) != y1.abs_diff(y2) {
+            return Err(AoCError::parse(
+                line_number, 1, "Line is neither orthogonal nor at 45°"
+            ));
+        }
+        Ok(line)
     }
 
     fn is_horizontal_or_vertical(&self) -> bool {
@@ -65,76 +86,27 @@ impl Line {
     }
 }
 
-struct Field {
-    cells: Vec<usize>,
-    lines: Vec<Line>,
-    width: usize,
-    height: usize
-}
-
-impl Field {
-    fn new(input: &str) -> Field {
-        let mut lines: Vec<Line> = Vec::new();
-        for text_line in input.lines() {
-            lines.push(Line::new(text_line));
-        }
-        let width: usize = std::cmp::max(
-            lines.iter().max_by_key(|line| line.start.x).unwrap().start.x,
-            lines.iter().max_by_key(|line| line.end.x).unwrap().end.x
-        ) + 1;
-        let height: usize = std::cmp::max(
-            lines.iter().max_by_key(|line| line.start.y).unwrap().start.y,
-            lines.iter().max_by_key(|line| line.end.y).unwrap().end.y
-        ) + 1;
-
-        let cells: Vec<usize> = vec![0; width * height];
-        Field {
-            cells,
-            lines,
-            width,
-            height
-        }
-    }
-
-    fn evaluate_intersections(&mut self, exclude_diagonal: bool) -> usize {
-        Self::evaluate_intersections_(&self.lines, &mut self.cells, self.width, exclude_diagonal)
-    }
-
-    fn evaluate_intersections_(lines: &[Line], cells: &mut [usize], width: usize, exclude_diagonal: bool) -> usize {
-        for line in lines {
-            let start_x = std::cmp::min(line.start.x, line.end.x);
-            let end_x = std::cmp::max(line.start.x, line.end.x);
-            if line.is_horizontal_or_vertical() {
-                let start_y = std::cmp::min(line.start.y, line.end.y);
-                let end_y = std::cmp::max(line.start.y, line.end.y);
-                // The following would be a rectangle if we didn't
-                // already know that we're only looking at orthogonal
-                // lines.
-                for x in start_x..end_x+1 {
-                    for y in start_y..end_y+1 {
-                        let prev = cells.get(y * width + x).expect(
-                            format!(
-                                "({}, {}) out of bounds for width {} and {} cells",
-                                x, y, width, cells.len()
-                            ).as_str()
-                        );
-                        cells[y * width + x] = prev + 1;
-                    }
-                }
-            } else if !exclude_diagonal {
-                let slope = (line.end.y as i64 - line.start.y as i64) / (line.end.x as i64 - line.start.x as i64);
-                let start_y = if slope > 0 { std::cmp::min(line.start.y, line.end.y) } else { std::cmp::max(line.start.y, line.end.y) };
-                for (i, x) in (start_x..end_x+1).enumerate() {
-                    // We know that diagonal lines will always be at 45 degree angles.
-                    let y = if slope > 0 { start_y + i } else { start_y - i };
-                    let prev = cells.get(y * width + x).unwrap();
-                    cells[y * width + x] = prev + 1;
-                }
-            }
+/// Counts the points where at least two lines of vents overlap. `lines`
+/// must not be empty.
+fn count_intersections(lines: &[Line], exclude_diagonal: bool) -> usize {
+    let width = lines.iter().map(|line| line.start.x.max(line.end.x)).max().unwrap() + 1;
+    let height = lines.iter().map(|line| line.start.y.max(line.end.y)).max().unwrap() + 1;
+    let mut field: Grid<usize> = Grid::filled(width, height, 0);
+    for line in lines {
+        if exclude_diagonal && !line.is_horizontal_or_vertical() { continue; }
+        let dir = (
+            (line.end.x as isize - line.start.x as isize).signum(),
+            (line.end.y as isize - line.start.y as isize).signum(),
+        );
+        // Lines are orthogonal or at 45°, so each step is one of the eight
+        // directions.
+        let len = line.start.x.abs_diff(line.end.x).max(line.start.y.abs_diff(line.end.y));
+        let mut pos = (line.start.x, line.start.y);
+        field[pos] += 1;
+        for _ in 0..len {
+            pos = field.step(pos, dir).unwrap();
+            field[pos] += 1;
         }
-
-        cells.iter().fold(0, |acc, cell| {
-            if *cell > 1 { acc + 1 } else { acc }
-        })
     }
+    field.iter().filter(|&&count| count > 1).count()
 }
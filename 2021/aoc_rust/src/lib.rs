@@ -0,0 +1,12 @@
+//! Solutions for Advent of Code 2021.
+
+#[macro_use]
+extern crate lazy_static;
+
+aoc_core::days! {
+    day01::Day01,
+    day02::Day02,
+    day03::Day03,
+    day04::Day04,
+    day05::Day05,
+}
@@ -0,0 +1,169 @@
+//! Expected answers that are stored next to the puzzle inputs in
+//! `<year>/<NN>/answers.toml`, e.g.
+//! ```toml
+//! part1 = 874
+//! part2 = "ABCDEF"
+//! ```
+//! Parts that have not been solved yet are simply left out.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use crate::input;
+use crate::solution::Part;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExpectedAnswers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+/// Answers may be written as TOML integers or strings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnswerValue {
+    Int(i64),
+    Str(String),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AnswersFile {
+    part1: Option<AnswerValue>,
+    part2: Option<AnswerValue>,
+}
+
+impl AnswerValue {
+    fn into_string(self) -> String {
+        match self {
+            AnswerValue::Int(n) => n.to_string(),
+            AnswerValue::Str(s) => s,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Io { path: PathBuf, source: io::Error },
+    Toml { path: PathBuf, source: toml::de::Error },
+    Write { path: PathBuf, source: io::Error },
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnswersError::Io { path, source } => write!(
+                f, "Could not read file \"{}\": {}", path.display(), source
+            ),
+            AnswersError::Toml { path, source } => write!(
+                f, "Invalid answers file \"{}\": {}", path.display(), source
+            ),
+            AnswersError::Write { path, source } => write!(
+                f, "Could not write file \"{}\": {}", path.display(), source
+            ),
+        }
+    }
+}
+
+impl Error for AnswersError {}
+
+impl ExpectedAnswers {
+    pub fn get(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.part1.as_deref(),
+            Part::Two => self.part2.as_deref(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<ExpectedAnswers, toml::de::Error> {
+        let file: AnswersFile = toml::from_str(text)?;
+        Ok(ExpectedAnswers {
+            part1: file.part1.map(AnswerValue::into_string),
+            part2: file.part2.map(AnswerValue::into_string),
+        })
+    }
+}
+
+/// The location of a day's answers file, e.g. `<root>/2020/05/answers.toml`.
+pub fn answers_path(root: &Path, year: u16, day: u8) -> PathBuf {
+    input::day_dir(root, year, day).join("answers.toml")
+}
+
+/// Reads the expected answers of a day. A missing file means that no
+/// answers are known yet, so it results in empty `ExpectedAnswers`.
+pub fn read_answers(path: &Path) -> Result<ExpectedAnswers, AnswersError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Ok(ExpectedAnswers::default());
+        },
+        Err(source) => {
+            return Err(AnswersError::Io { path: path.to_path_buf(), source });
+        }
+    };
+    ExpectedAnswers::parse(&text)
+        .map_err(|source| AnswersError::Toml { path: path.to_path_buf(), source })
+}
+
+/// Stores an accepted answer in an answers file, replacing an earlier answer
+/// to the same part. Other lines, e.g. comments, are kept.
+pub fn record_answer(path: &Path, part: Part, answer: &str) -> Result<(), AnswersError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(source) => {
+            return Err(AnswersError::Io { path: path.to_path_buf(), source });
+        }
+    };
+    let key = format!("part{}", part);
+    let mut lines: Vec<String> = text.lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            !(line.starts_with(&key) && line[key.len()..].trim_start().starts_with('='))
+        })
+        .map(|line| line.to_string())
+        .collect();
+    let value = match answer.parse::<i64>() {
+        Ok(n) => n.to_string(),
+        Err(_) => toml::Value::String(answer.to_string()).to_string(),
+    };
+    lines.push(format!("{} = {}", key, value));
+    fs::write(path, lines.join("\n") + "\n")
+        .map_err(|source| AnswersError::Write { path: path.to_path_buf(), source })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+
+    #[test]
+    fn test_parse() {
+        let answers = ExpectedAnswers::parse("part1 = 874\npart2 = \"abc\"\n")
+            .unwrap();
+        assert_eq!(answers.get(Part::One), Some("874"));
+        assert_eq!(answers.get(Part::Two), Some("abc"));
+
+        let answers = ExpectedAnswers::parse("part1 = -3\n").unwrap();
+        assert_eq!(answers.get(Part::One), Some("-3"));
+        assert_eq!(answers.get(Part::Two), None);
+
+        assert!(ExpectedAnswers::parse("part3 = 1\n").is_err());
+    }
+
+    #[test]
+    fn test_record_answer() {
+        let dir = TempDir::new("answers");
+        let path = dir.path().join("answers.toml");
+        fs::write(&path, "# Comment\npart1 = 1\n").unwrap();
+        record_answer(&path, Part::Two, "a\"b").unwrap();
+        record_answer(&path, Part::One, "874").unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(text, "# Comment\npart2 = 'a\"b'\npart1 = 874\n");
+        let answers = ExpectedAnswers::parse(&text).unwrap();
+        assert_eq!(answers.get(Part::Two), Some("a\"b"));
+    }
+}
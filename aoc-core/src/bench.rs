@@ -0,0 +1,294 @@
+//! Repeated timing of the parse and solve phases of the solutions, with
+//! medians and percentiles instead of the single measurement of `run`.
+//!
+//! The statistics can be written as JSON and later passed back in as a
+//! baseline, so that the medians of a change can be compared with those
+//! from before.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Deserializer, Serialize};
+use crate::provider::InputProvider;
+use crate::runner::{self, RunError};
+use crate::solution::{Answer, DynSolution, Part, Registry};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Part1 => write!(f, "part 1"),
+            Phase::Part2 => write!(f, "part 2"),
+        }
+    }
+}
+
+/// The timings of one phase of one day.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PhaseStats {
+    pub year: u16,
+    pub day: u8,
+    pub phase: Phase,
+    pub samples: usize,
+    #[serde(rename = "min_ms", serialize_with = "runner::serialize_millis",
+            deserialize_with = "deserialize_millis")]
+    pub min: Duration,
+    #[serde(rename = "p10_ms", serialize_with = "runner::serialize_millis",
+            deserialize_with = "deserialize_millis")]
+    pub p10: Duration,
+    #[serde(rename = "median_ms", serialize_with = "runner::serialize_millis",
+            deserialize_with = "deserialize_millis")]
+    pub median: Duration,
+    #[serde(rename = "p90_ms", serialize_with = "runner::serialize_millis",
+            deserialize_with = "deserialize_millis")]
+    pub p90: Duration,
+    #[serde(rename = "max_ms", serialize_with = "runner::serialize_millis",
+            deserialize_with = "deserialize_millis")]
+    pub max: Duration,
+}
+
+fn deserialize_millis<'de, D: Deserializer<'de>>(
+    deserializer: D
+) -> Result<Duration, D::Error> {
+    let millis = f64::deserialize(deserializer)?;
+    Duration::try_from_secs_f64(millis / 1000.0).map_err(serde::de::Error::custom)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BenchOptions {
+    /// How often each phase is timed, at most.
+    pub samples: usize,
+    /// Stop sampling a phase once this much time has been spent on it, so
+    /// that slow days don't take forever. Each phase is timed at least once.
+    pub budget: Duration,
+}
+
+impl Default for BenchOptions {
+    fn default() -> BenchOptions {
+        BenchOptions { samples: 10, budget: Duration::from_secs(10) }
+    }
+}
+
+#[derive(Debug)]
+pub enum BaselineError {
+    Io { path: PathBuf, source: io::Error },
+    Json { path: PathBuf, source: serde_json::Error },
+}
+
+impl fmt::Display for BaselineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BaselineError::Io { path, source } => write!(
+                f, "Could not read file \"{}\": {}", path.display(), source
+            ),
+            BaselineError::Json { path, source } => write!(
+                f, "Invalid baseline file \"{}\": {}", path.display(), source
+            ),
+        }
+    }
+}
+
+impl Error for BaselineError {}
+
+/// The nearest-rank percentile of sorted samples.
+pub fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    assert!(!sorted.is_empty(), "No samples");
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn stats(year: u16, day: u8, phase: Phase, mut samples: Vec<Duration>) -> PhaseStats {
+    samples.sort();
+    PhaseStats {
+        year,
+        day,
+        phase,
+        samples: samples.len(),
+        min: samples[0],
+        p10: percentile(&samples, 10.0),
+        median: percentile(&samples, 50.0),
+        p90: percentile(&samples, 90.0),
+        max: samples[samples.len() - 1],
+    }
+}
+
+fn sample<F: FnMut()>(options: &BenchOptions, mut f: F) -> Vec<Duration> {
+    let mut samples: Vec<Duration> = Vec::new();
+    let mut spent = Duration::ZERO;
+    while samples.is_empty() || (samples.len() < options.samples && spent < options.budget) {
+        let start = Instant::now();
+        f();
+        let elapsed = start.elapsed();
+        spent += elapsed;
+        samples.push(elapsed);
+    }
+    samples
+}
+
+/// Times each phase of a day on the given input. Each phase is run once
+/// before sampling to catch errors and to warm up caches. Parts that are
+/// not solved yet are left out.
+pub fn bench_day(
+    solution: &dyn DynSolution,
+    puzzle_input: &str,
+    options: &BenchOptions,
+) -> Result<Vec<PhaseStats>, RunError> {
+    let (year, day) = (solution.year(), solution.day());
+    let parsed = solution.parse_any(puzzle_input)
+        .map_err(|source| RunError::Solve { year, day, part: None, source })?;
+    let mut all_stats = vec![stats(year, day, Phase::Parse, sample(options, || {
+        let _ = black_box(solution.parse_any(black_box(puzzle_input)));
+    }))];
+    for (part, phase) in [(Part::One, Phase::Part1), (Part::Two, Phase::Part2)] {
+        let answer = solution.part_any(parsed.as_ref(), part)
+            .map_err(|source| RunError::Solve { year, day, part: Some(part), source })?;
+        if answer == Answer::Unsolved { continue; }
+        all_stats.push(stats(year, day, phase, sample(options, || {
+            let _ = black_box(solution.part_any(black_box(parsed.as_ref()), part));
+        })));
+    }
+    Ok(all_stats)
+}
+
+/// Benchmarks every registered day, optionally only those of the given year
+/// and day, on their puzzle inputs.
+pub fn bench_all(
+    registry: &Registry,
+    inputs: &dyn InputProvider,
+    year: Option<u16>,
+    day: Option<u8>,
+    options: &BenchOptions,
+) -> Vec<Result<Vec<PhaseStats>, RunError>> {
+    registry.days()
+        .filter(|(y, _)| year.is_none() || year == Some(*y))
+        .filter(|(_, d)| day.is_none() || day == Some(*d))
+        .map(|(y, d)| {
+            let solution = registry.get(y, d).unwrap();
+            bench_day(solution, &inputs.input(y, d)?, options)
+        })
+        .collect()
+}
+
+pub fn read_baseline(path: &Path) -> Result<Vec<PhaseStats>, BaselineError> {
+    let text = fs::read_to_string(path)
+        .map_err(|source| BaselineError::Io { path: path.to_path_buf(), source })?;
+    serde_json::from_str(&text)
+        .map_err(|source| BaselineError::Json { path: path.to_path_buf(), source })
+}
+
+/// Writes the statistics as a JSON array that can be read back as a
+/// baseline.
+pub fn write_json<W: Write>(out: &mut W, stats: &[PhaseStats]) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, stats)?;
+    writeln!(out)
+}
+
+/// Writes a table with one row per phase. With a baseline, the medians are
+/// compared with those of the same phases in the baseline.
+pub fn write_table<W: Write>(
+    out: &mut W,
+    stats: &[PhaseStats],
+    baseline: Option<&[PhaseStats]>,
+) -> io::Result<()> {
+    let mut header = vec!["Year", "Day", "Phase", "Samples", "Median", "P10", "P90"];
+    if baseline.is_some() {
+        header.extend(["Baseline", "Change"]);
+    }
+    let rows: Vec<Vec<String>> = stats.iter().map(|s| {
+        let mut row = vec![
+            s.year.to_string(),
+            s.day.to_string(),
+            s.phase.to_string(),
+            s.samples.to_string(),
+            runner::format_duration(s.median),
+            runner::format_duration(s.p10),
+            runner::format_duration(s.p90),
+        ];
+        if let Some(baseline) = baseline {
+            let old = baseline.iter()
+                .find(|b| (b.year, b.day, b.phase) == (s.year, s.day, s.phase));
+            match old {
+                Some(old) => {
+                    row.push(runner::format_duration(old.median));
+                    // A baseline median of zero, e.g. rounded down in the
+                    // JSON, has no relative change.
+                    if old.median.is_zero() {
+                        row.push(String::from("n/a"));
+                    } else {
+                        let change = s.median.as_secs_f64() / old.median.as_secs_f64() - 1.0;
+                        row.push(format!("{:+.1}%", change * 100.0));
+                    }
+                },
+                None => row.extend([String::from("-"), String::from("-")]),
+            }
+        }
+        row
+    }).collect();
+    runner::write_columns(out, &header, &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_stats() {
+        let samples: Vec<Duration> = (1..=10).rev().map(ms).collect();
+        let stats = stats(2020, 15, Phase::Part2, samples);
+        assert_eq!(stats.samples, 10);
+        assert_eq!((stats.min, stats.max), (ms(1), ms(10)));
+        assert_eq!((stats.p10, stats.median, stats.p90), (ms(1), ms(5), ms(9)));
+        assert_eq!(percentile(&[ms(3)], 90.0), ms(3));
+    }
+
+    #[test]
+    fn test_sample_budget() {
+        let options = BenchOptions { samples: 100, budget: Duration::ZERO };
+        assert_eq!(sample(&options, || {}).len(), 1);
+        let options = BenchOptions { samples: 3, budget: Duration::from_secs(60) };
+        assert_eq!(sample(&options, || {}).len(), 3);
+    }
+
+    #[test]
+    fn test_baseline_comparison() {
+        let new = stats(2020, 10, Phase::Part1, vec![ms(3)]);
+        let old = stats(2020, 10, Phase::Part1, vec![ms(4)]);
+        let parse = stats(2020, 10, Phase::Parse, vec![ms(1)]);
+
+        let mut json: Vec<u8> = Vec::new();
+        write_json(&mut json, std::slice::from_ref(&old)).unwrap();
+        let baseline: Vec<PhaseStats> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(baseline, vec![old]);
+
+        let mut out: Vec<u8> = Vec::new();
+        write_table(&mut out, &[parse, new], Some(&baseline)).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "\
+Year | Day |  Phase | Samples | Median |    P10 |    P90 | Baseline | Change
+-----+-----+--------+---------+--------+--------+--------+----------+-------
+2020 |  10 |  parse |       1 | 1.00ms | 1.00ms | 1.00ms |        - |      -
+2020 |  10 | part 1 |       1 | 3.00ms | 3.00ms | 3.00ms |   4.00ms | -25.0%
+");
+
+        let old = stats(2020, 10, Phase::Part1, vec![Duration::ZERO]);
+        let new = stats(2020, 10, Phase::Part1, vec![ms(3)]);
+        let mut out: Vec<u8> = Vec::new();
+        write_table(&mut out, &[new], Some(&[old])).unwrap();
+        assert!(String::from_utf8(out).unwrap().ends_with("|    0.0µs |    n/a\n"));
+    }
+}
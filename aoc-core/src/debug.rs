@@ -0,0 +1,27 @@
+//! Debug output of the solutions.
+//!
+//! Answers are returned to the runner, so anything a solution prints on its
+//! own is debug chatter. It goes to stderr, and only if enabled with
+//! `set_verbose`, so it never ends up in the answer stream.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Like `eprintln!`, but only prints in verbose mode.
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::debug::is_verbose() {
+            eprintln!($($arg)*);
+        }
+    };
+}
@@ -0,0 +1,80 @@
+use std::error::Error;
+use std::fmt;
+
+/// Everything that can go wrong while parsing a puzzle input or solving
+/// one of its parts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AoCError {
+    /// The input does not have the expected format. The position is a
+    /// 1-based (line, column) pair, if the problem can be pinned down to one.
+    Parse {
+        position: Option<(usize, usize)>,
+        message: String,
+    },
+    /// The input is well-formed, but the puzzle has no answer for it.
+    NoSolution(String),
+}
+
+impl AoCError {
+    /// A parse error at the given 1-based line and column.
+    pub fn parse(line: usize, column: usize, message: impl Into<String>) -> AoCError {
+        AoCError::Parse { position: Some((line, column)), message: message.into() }
+    }
+
+    /// A parse error at the given byte offset into `text`.
+    pub fn parse_at(text: &str, offset: usize, message: impl Into<String>) -> AoCError {
+        let before = &text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        AoCError::parse(line, column, message)
+    }
+
+    /// A parse error that concerns the input as a whole, e.g. a missing
+    /// section.
+    pub fn malformed(message: impl Into<String>) -> AoCError {
+        AoCError::Parse { position: None, message: message.into() }
+    }
+
+    /// A parse error for a puzzle parameter that the solution does not have.
+    pub fn unknown_param(name: &str) -> AoCError {
+        AoCError::malformed(format!("Unknown parameter \"{}\"", name))
+    }
+
+    pub fn no_solution(message: impl Into<String>) -> AoCError {
+        AoCError::NoSolution(message.into())
+    }
+}
+
+impl fmt::Display for AoCError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AoCError::Parse { position: Some((line, column)), message } => write!(
+                f, "Parse error at line {}, column {}: {}", line, column, message
+            ),
+            AoCError::Parse { position: None, message } => write!(
+                f, "Parse error: {}", message
+            ),
+            AoCError::NoSolution(message) => write!(f, "No solution: {}", message),
+        }
+    }
+}
+
+impl Error for AoCError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_at() {
+        let text = "abc\nde\nfgh";
+        assert_eq!(AoCError::parse_at(text, 0, "x"), AoCError::parse(1, 1, "x"));
+        assert_eq!(AoCError::parse_at(text, 5, "x"), AoCError::parse(2, 2, "x"));
+        assert_eq!(AoCError::parse_at(text, 7, "x"), AoCError::parse(3, 1, "x"));
+        assert_eq!(
+            AoCError::parse(2, 3, "Unexpected \"x\"").to_string(),
+            "Parse error at line 2, column 3: Unexpected \"x\""
+        );
+    }
+}
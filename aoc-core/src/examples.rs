@@ -0,0 +1,218 @@
+//! The example inputs from the puzzle descriptions, stored next to the real
+//! input as `<year>/<NN>/example<N>.txt`. The expected answers for an
+//! example go into `example<N>.toml`, which has the same format as
+//! `answers.toml`. Only the parts listed there are checked, since examples
+//! are often only meant for one of the parts. Puzzle parameters that differ
+//! from the real input go into a `[params]` table of the same file:
+//!
+//! ```toml
+//! part1 = 127
+//!
+//! [params]
+//! preamble = 5
+//! ```
+//!
+//! The `days!` macro generates one test per day that checks all of its
+//! examples, so `cargo test` covers every registered solution.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::answers::{AnswersError, ExpectedAnswers};
+use crate::input::{self, InputSource};
+use crate::runner;
+use crate::solution::{DynSolution, Params, Part};
+use crate::verify::{self, Verdict};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Example {
+    /// The part of the file name between "example" and ".txt".
+    pub name: String,
+    pub input: PathBuf,
+    pub expected: ExpectedAnswers,
+    pub params: Params,
+}
+
+/// The location of the expected answers of an example, e.g.
+/// `<root>/2020/05/example1.toml` for the name "1".
+pub fn example_answers_path(root: &Path, year: u16, day: u8, name: &str) -> PathBuf {
+    input::day_dir(root, year, day).join(format!("example{}.toml", name))
+}
+
+/// Finds all examples of a day, ordered by name ("2" before "10").
+pub fn find_examples(root: &Path, year: u16, day: u8) -> Result<Vec<Example>, AnswersError> {
+    let dir = input::day_dir(root, year, day);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => return Err(AnswersError::Io { path: dir, source }),
+    };
+    let mut names: Vec<String> = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|source| AnswersError::Io { path: dir.clone(), source })?;
+        let file_name = entry.file_name();
+        let name = file_name.to_str()
+            .and_then(|f| f.strip_prefix("example"))
+            .and_then(|f| f.strip_suffix(".txt"));
+        if let Some(name) = name {
+            names.push(name.to_string());
+        }
+    }
+    names.sort_by(|a, b| (a.len(), a).cmp(&(b.len(), b)));
+    names.iter().map(|name| read_example(root, year, day, name)).collect()
+}
+
+/// Reads the expected answers and parameters of an example. Both are empty
+/// if the example has no answers file.
+pub fn read_example(root: &Path, year: u16, day: u8, name: &str) -> Result<Example, AnswersError> {
+    let path = example_answers_path(root, year, day, name);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(source) => return Err(AnswersError::Io { path, source }),
+    };
+    let toml_error = |source| AnswersError::Toml { path: path.clone(), source };
+    let mut table: toml::Table = toml::from_str(&text).map_err(toml_error)?;
+    let params: Params = match table.remove("params") {
+        Some(params) => params.try_into().map_err(toml_error)?,
+        None => Params::new(),
+    };
+    let expected = ExpectedAnswers::parse(&table.to_string()).map_err(toml_error)?;
+    Ok(Example {
+        name: name.to_string(),
+        input: input::example_path(root, year, day, name),
+        expected,
+        params,
+    })
+}
+
+/// Runs a solution on each of its examples. Returns the number of checked
+/// parts, or a description of every failed part and every example that
+/// could not be run. A day without any expected example answers fails too,
+/// since its test would check nothing.
+pub fn check_examples(
+    solution: &dyn DynSolution,
+    root: &Path,
+) -> Result<usize, Vec<String>> {
+    let (year, day) = (solution.year(), solution.day());
+    let examples = find_examples(root, year, day).map_err(|e| vec![e.to_string()])?;
+    let mut num_checked = 0;
+    let mut failures: Vec<String> = Vec::new();
+    for example in examples {
+        let file_name = format!("example{}.txt", example.name);
+        // Without expected answers, the example is only parsed.
+        let parts: Vec<Part> = [Part::One, Part::Two].into_iter()
+            .filter(|&part| example.expected.get(part).is_some())
+            .collect();
+        let result = InputSource::File(example.input).read()
+            .map_err(|e| format!("Could not read {}: {}", file_name, e))
+            .and_then(|puzzle_input| runner::solve_with(solution, &puzzle_input, &example.params, &parts)
+                .map_err(|e| format!("{}: {}", file_name, e)));
+        match result {
+            Ok(result) => {
+                for verdict in verify::verify(&result, &example.expected) {
+                    num_checked += 1;
+                    if let Verdict::Fail { .. } = verdict.verdict {
+                        failures.push(format!("{}: {}", file_name, verdict));
+                    }
+                }
+            },
+            Err(e) => failures.push(e),
+        }
+    }
+    if num_checked == 0 && failures.is_empty() {
+        failures.push(format!(
+            "{} day {}: No example answers in {}",
+            year, day, input::day_dir(root, year, day).display()
+        ));
+    }
+    if failures.is_empty() { Ok(num_checked) } else { Err(failures) }
+}
+
+/// Like `check_examples`, but panics with a report of all failures, for
+/// use in tests. `manifest_dir` is the directory of the year's crate, below
+/// the root that holds the puzzle folders.
+pub fn assert_examples(solution: &dyn DynSolution, manifest_dir: &str) {
+    let year_dir = solution.year().to_string();
+    let root = Path::new(manifest_dir).ancestors()
+        .find(|dir| dir.join(&year_dir).is_dir())
+        .unwrap_or_else(|| panic!("No {}/ folder above {}", year_dir, manifest_dir));
+    if let Err(failures) = check_examples(solution, root) {
+        panic!("\n{}\n", failures.join("\n"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AoCError;
+    use crate::solution::{Answer, Solution};
+    use crate::test_dir::TempDir;
+
+    struct Sum;
+
+    impl Solution for Sum {
+        const YEAR: u16 = 1970;
+        const DAY: u8 = 1;
+
+        type Input = Vec<u64>;
+
+        fn parse(&self, input: &str) -> Result<Vec<u64>, AoCError> {
+            crate::parse::numbers(input)
+        }
+
+        fn part1(&self, input: &Vec<u64>) -> Result<Answer, AoCError> {
+            Ok(input.iter().sum::<u64>().into())
+        }
+
+        fn part2(&self, input: &Vec<u64>) -> Result<Answer, AoCError> {
+            Ok(input.iter().product::<u64>().into())
+        }
+    }
+
+    #[test]
+    fn test_check_examples() {
+        let temp = TempDir::new("examples");
+        let root = temp.path();
+        let dir = input::day_dir(root, 1970, 1);
+        fs::create_dir_all(&dir).unwrap();
+        // Only parsed, without expected answers.
+        fs::write(dir.join("example1.txt"), "2\n3\n").unwrap();
+
+        let failures = check_examples(&Sum, root).unwrap_err();
+        assert_eq!(failures, vec![format!("1970 day 1: No example answers in {}", dir.display())]);
+
+        fs::write(dir.join("example1.toml"), "part1 = 5\npart2 = 6\n").unwrap();
+        fs::write(dir.join("example10.txt"), "4\n").unwrap();
+        fs::write(dir.join("example10.toml"), "part2 = 5\n").unwrap();
+        fs::write(dir.join("example2.txt"), "x\n").unwrap();
+        let names: Vec<String> = find_examples(root, 1970, 1).unwrap()
+            .into_iter().map(|example| example.name).collect();
+        assert_eq!(names, vec!["1", "2", "10"]);
+        let failures = check_examples(&Sum, root).unwrap_err();
+        assert_eq!(failures, vec![
+            "example2.txt: 1970 day 1: Parse error at line 1, column 1: \
+             Invalid value \"x\": invalid digit found in string",
+            "example10.txt: 1970 day 1 part 2: FAIL (got 4, expected 5)",
+        ]);
+    }
+
+    #[test]
+    fn test_example_params() {
+        let temp = TempDir::new("example-params");
+        let root = temp.path();
+        let dir = input::day_dir(root, 1970, 1);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("example1.txt"), "2\n3\n").unwrap();
+        fs::write(dir.join("example1.toml"), "part1 = 5\n\n[params]\nsize = 7\n").unwrap();
+
+        let example = read_example(root, 1970, 1, "1").unwrap();
+        assert_eq!(example.expected.get(Part::One), Some("5"));
+        assert_eq!(example.params, Params::from([("size".to_string(), 7)]));
+        // `Sum` has no parameters.
+        let failures = check_examples(&Sum, root).unwrap_err();
+        assert_eq!(failures, vec![
+            "example1.txt: 1970 day 1: Parse error: Unknown parameter \"size\"",
+        ]);
+    }
+}
@@ -0,0 +1,571 @@
+//! Downloading puzzle inputs from the Advent of Code site and submitting
+//! answers to it.
+//!
+//! The site identifies users by their session cookie, which is read from
+//! the `AOC_SESSION` environment variable or from `<root>/.session`.
+//! Requests are spaced out by a `RateLimiter`, and inputs are only
+//! downloaded once since `CachedInputs` stores them next to the solutions.
+//! The verdicts on submitted answers are kept in
+//! `<year>/<NN>/submissions.json`, so the same wrong answer is never
+//! submitted twice, and correct answers are recorded in `answers.toml`.
+
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::answers::{self, AnswersError};
+use crate::input;
+use crate::solution::Part;
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+
+/// The environment variable that holds the session cookie.
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+/// The minimum time between two requests to the site.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+
+const USER_AGENT: &str = concat!(
+    "aoc/", env!("CARGO_PKG_VERSION"), " (", env!("CARGO_PKG_AUTHORS"), ")"
+);
+
+#[derive(Debug)]
+pub enum FetchError {
+    Status { url: String, status: u16 },
+    Transport { url: String, message: String },
+    /// The page returned for a submission did not contain a known verdict.
+    UnknownResponse { url: String },
+    Read { path: PathBuf, source: io::Error },
+    Write { path: PathBuf, source: io::Error },
+    Json { path: PathBuf, source: serde_json::Error },
+    Answers(Box<AnswersError>),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::Status { url, status } => {
+                write!(f, "Request to {} failed with status {}", url, status)?;
+                match status {
+                    400 | 500 => write!(f, " (is the session cookie still valid?)"),
+                    404 => write!(f, " (is the puzzle unlocked yet?)"),
+                    _ => Ok(()),
+                }
+            },
+            FetchError::Transport { url, message } => write!(
+                f, "Request to {} failed: {}", url, message
+            ),
+            FetchError::UnknownResponse { url } => write!(
+                f, "Could not find a verdict in the response from {}", url
+            ),
+            FetchError::Read { path, source } => write!(
+                f, "Could not read file \"{}\": {}", path.display(), source
+            ),
+            FetchError::Write { path, source } => write!(
+                f, "Could not write file \"{}\": {}", path.display(), source
+            ),
+            FetchError::Json { path, source } => write!(
+                f, "Invalid submissions file \"{}\": {}", path.display(), source
+            ),
+            FetchError::Answers(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+impl Error for FetchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FetchError::Read { source, .. } => Some(source),
+            FetchError::Write { source, .. } => Some(source),
+            FetchError::Json { source, .. } => Some(source),
+            FetchError::Answers(source) => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// The verdict of the site on a submitted answer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Submission {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint in which direction.
+    Incorrect,
+    /// The part was solved before, so the answer was not checked.
+    AlreadySolved,
+    /// Another answer was submitted too recently. Never logged.
+    #[serde(skip)]
+    TooSoon { wait: Option<Duration> },
+}
+
+impl Submission {
+    /// Whether the verdict says something about the answer itself, so that
+    /// it is worth remembering.
+    fn is_final(self) -> bool {
+        !matches!(self, Submission::AlreadySolved | Submission::TooSoon { .. })
+    }
+}
+
+impl fmt::Display for Submission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Submission::Correct => write!(f, "correct"),
+            Submission::TooHigh => write!(f, "wrong (too high)"),
+            Submission::TooLow => write!(f, "wrong (too low)"),
+            Submission::Incorrect => write!(f, "wrong"),
+            Submission::AlreadySolved => write!(f, "not checked, the part is solved already"),
+            Submission::TooSoon { wait: Some(wait) } => write!(
+                f, "not checked, wait {}s before submitting again", wait.as_secs()
+            ),
+            Submission::TooSoon { wait: None } => write!(
+                f, "not checked, wait before submitting again"
+            ),
+        }
+    }
+}
+
+/// Extracts the verdict from the page that the site returns for a
+/// submission, or `None` if the page contains none of the known messages.
+pub fn parse_submission(html: &str) -> Option<Submission> {
+    let article = match (html.find("<article"), html.find("</article>")) {
+        (Some(start), Some(end)) if start < end => &html[start..end],
+        _ => html,
+    };
+    let text = strip_tags(article);
+    if text.contains("That's the right answer") {
+        Some(Submission::Correct)
+    } else if text.contains("That's not the right answer") {
+        if text.contains("too high") {
+            Some(Submission::TooHigh)
+        } else if text.contains("too low") {
+            Some(Submission::TooLow)
+        } else {
+            Some(Submission::Incorrect)
+        }
+    } else if text.contains("You gave an answer too recently") {
+        Some(Submission::TooSoon { wait: parse_wait(&text) })
+    } else if text.contains("You don't seem to be solving the right level") {
+        Some(Submission::AlreadySolved)
+    } else {
+        None
+    }
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Parses e.g. "You have 1m 5s left to wait".
+fn parse_wait(text: &str) -> Option<Duration> {
+    let start = text.find("You have ")? + "You have ".len();
+    let end = start + text[start..].find(" left to wait")?;
+    let mut secs = 0;
+    for amount in text[start..end].split_whitespace() {
+        let unit = amount.chars().last()?;
+        let n: u64 = amount[..amount.len() - unit.len_utf8()].parse().ok()?;
+        secs += n * match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            _ => return None,
+        };
+    }
+    Some(Duration::from_secs(secs))
+}
+
+/// Spaces out requests so that they are at least `interval` apart.
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: Duration) -> RateLimiter {
+        RateLimiter { interval, last_request: Mutex::new(None) }
+    }
+
+    /// Blocks until the next request may be sent.
+    pub fn wait(&self) {
+        let mut last_request = self.last_request.lock().unwrap();
+        if let Some(last) = *last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.interval {
+                thread::sleep(self.interval - elapsed);
+            }
+        }
+        *last_request = Some(Instant::now());
+    }
+}
+
+/// A source of puzzle inputs and a judge of answers, normally the site
+/// itself.
+pub trait Fetcher {
+    fn fetch_input(&self, year: u16, day: u8) -> Result<String, FetchError>;
+
+    fn submit_answer(
+        &self,
+        year: u16,
+        day: u8,
+        part: Part,
+        answer: &str,
+    ) -> Result<Submission, FetchError>;
+}
+
+impl<F: Fetcher + ?Sized> Fetcher for &F {
+    fn fetch_input(&self, year: u16, day: u8) -> Result<String, FetchError> {
+        (**self).fetch_input(year, day)
+    }
+
+    fn submit_answer(
+        &self,
+        year: u16,
+        day: u8,
+        part: Part,
+        answer: &str,
+    ) -> Result<Submission, FetchError> {
+        (**self).submit_answer(year, day, part, answer)
+    }
+}
+
+/// Talks to the site, or to any server at `base_url` that behaves like it.
+pub struct HttpFetcher {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    limiter: RateLimiter,
+}
+
+impl HttpFetcher {
+    pub fn new(session: &str) -> HttpFetcher {
+        HttpFetcher {
+            agent: ureq::AgentBuilder::new()
+                .user_agent(USER_AGENT)
+                .timeout(Duration::from_secs(30))
+                .build(),
+            base_url: BASE_URL.to_string(),
+            session: session.to_string(),
+            limiter: RateLimiter::new(DEFAULT_INTERVAL),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> HttpFetcher {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> HttpFetcher {
+        self.limiter = RateLimiter::new(interval);
+        self
+    }
+
+    fn day_url(&self, year: u16, day: u8) -> String {
+        format!("{}/{}/day/{}", self.base_url, year, day)
+    }
+
+    fn read_response(
+        url: String,
+        response: Result<ureq::Response, ureq::Error>,
+    ) -> Result<String, FetchError> {
+        match response {
+            Ok(response) => response.into_string()
+                .map_err(|e| FetchError::Transport { url, message: e.to_string() }),
+            Err(ureq::Error::Status(status, _)) => Err(FetchError::Status { url, status }),
+            Err(ureq::Error::Transport(e)) => Err(
+                FetchError::Transport { url, message: e.to_string() }
+            ),
+        }
+    }
+}
+
+impl Fetcher for HttpFetcher {
+    fn fetch_input(&self, year: u16, day: u8) -> Result<String, FetchError> {
+        let url = format!("{}/input", self.day_url(year, day));
+        self.limiter.wait();
+        let response = self.agent.get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .call();
+        HttpFetcher::read_response(url, response)
+    }
+
+    fn submit_answer(
+        &self,
+        year: u16,
+        day: u8,
+        part: Part,
+        answer: &str,
+    ) -> Result<Submission, FetchError> {
+        let url = format!("{}/answer", self.day_url(year, day));
+        self.limiter.wait();
+        let response = self.agent.post(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .send_form(&[("level", &part.to_string()), ("answer", answer)]);
+        let page = HttpFetcher::read_response(url.clone(), response)?;
+        parse_submission(&page).ok_or(FetchError::UnknownResponse { url })
+    }
+}
+
+/// The session cookie from the `AOC_SESSION` environment variable, or from
+/// the file `<root>/.session`.
+pub fn session_token(root: &Path) -> Option<String> {
+    let token = env::var(SESSION_VAR).ok()
+        .or_else(|| fs::read_to_string(root.join(".session")).ok())?;
+    let token = token.trim();
+    if token.is_empty() { None } else { Some(token.to_string()) }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct LoggedSubmission {
+    part: u8,
+    answer: String,
+    result: Submission,
+}
+
+/// The location of a day's submission log, e.g.
+/// `<root>/2020/05/submissions.json`.
+pub fn submissions_path(root: &Path, year: u16, day: u8) -> PathBuf {
+    input::day_dir(root, year, day).join("submissions.json")
+}
+
+fn read_log(path: &Path) -> Result<Vec<LoggedSubmission>, FetchError> {
+    match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|source| FetchError::Json { path: path.to_path_buf(), source }),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(source) => Err(FetchError::Read { path: path.to_path_buf(), source }),
+    }
+}
+
+fn write_log(path: &Path, log: &[LoggedSubmission]) -> Result<(), FetchError> {
+    let json = serde_json::to_string_pretty(log)
+        .map_err(|source| FetchError::Json { path: path.to_path_buf(), source })?;
+    fs::write(path, json + "\n")
+        .map_err(|source| FetchError::Write { path: path.to_path_buf(), source })
+}
+
+/// Submits an answer, unless its verdict is known already from
+/// `answers.toml` or from the submission log. New verdicts are logged and
+/// correct answers are recorded in `answers.toml`. Returns the verdict and
+/// whether it was known already.
+pub fn submit(
+    fetcher: &dyn Fetcher,
+    root: &Path,
+    year: u16,
+    day: u8,
+    part: Part,
+    answer: &str,
+) -> Result<(Submission, bool), FetchError> {
+    let answers_path = answers::answers_path(root, year, day);
+    let expected = answers::read_answers(&answers_path).map_err(|e| FetchError::Answers(Box::new(e)))?;
+    if expected.get(part) == Some(answer) {
+        return Ok((Submission::Correct, true));
+    }
+    let log_path = submissions_path(root, year, day);
+    let mut log = read_log(&log_path)?;
+    let known = log.iter()
+        .find(|logged| logged.part == part.number() && logged.answer == answer);
+    if let Some(logged) = known {
+        return Ok((logged.result, true));
+    }
+
+    let result = fetcher.submit_answer(year, day, part, answer)?;
+    if result.is_final() {
+        log.push(LoggedSubmission {
+            part: part.number(),
+            answer: answer.to_string(),
+            result,
+        });
+        write_log(&log_path, &log)?;
+    }
+    if result == Submission::Correct {
+        answers::record_answer(&answers_path, part, answer).map_err(|e| FetchError::Answers(Box::new(e)))?;
+    }
+    Ok((result, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use crate::test_dir::TempDir;
+
+    /// Answers one connection per response with the given status and body,
+    /// and returns the base URL and a handle to the received requests.
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            responses.into_iter().map(|(status, body)| {
+                let (stream, _) = listener.accept().unwrap();
+                let request = read_request(&stream);
+                write!(
+                    &stream,
+                    "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body
+                ).unwrap();
+                request
+            }).collect()
+        });
+        (url, handle)
+    }
+
+    fn read_request(stream: &TcpStream) -> String {
+        let mut reader = BufReader::new(stream);
+        let mut request = String::new();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() { break; }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            request.push_str(&line);
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        request + &String::from_utf8(body).unwrap()
+    }
+
+    const TOO_HIGH: &str = "<html><main><article><p>That's not the right answer; \
+        your answer is too high.  If you're stuck, ...</p></article></main></html>";
+
+    #[test]
+    fn test_fetch_input() {
+        let (url, server) = serve(vec![(200, "1\n2\n"), (404, "Not found")]);
+        let fetcher = HttpFetcher::new("abc")
+            .with_base_url(&url)
+            .with_interval(Duration::ZERO);
+        assert_eq!(fetcher.fetch_input(2020, 5).unwrap(), "1\n2\n");
+        assert!(matches!(
+            fetcher.fetch_input(2020, 26),
+            Err(FetchError::Status { status: 404, .. })
+        ));
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /2020/day/5/input HTTP/1.1\r\n"));
+        assert!(requests[0].to_lowercase().contains("\r\ncookie: session=abc\r\n"));
+    }
+
+    #[test]
+    fn test_submit_answer() {
+        let (url, server) = serve(vec![(200, TOO_HIGH)]);
+        let fetcher = HttpFetcher::new("abc")
+            .with_base_url(&url)
+            .with_interval(Duration::ZERO);
+        assert_eq!(
+            fetcher.submit_answer(2021, 3, Part::Two, "42").unwrap(),
+            Submission::TooHigh
+        );
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /2021/day/3/answer HTTP/1.1\r\n"));
+        assert!(requests[0].ends_with("\r\nlevel=2&answer=42"));
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let (url, server) = serve(vec![(200, "1"), (200, "2")]);
+        let fetcher = HttpFetcher::new("abc")
+            .with_base_url(&url)
+            .with_interval(Duration::from_millis(200));
+        let start = Instant::now();
+        fetcher.fetch_input(2020, 1).unwrap();
+        fetcher.fetch_input(2020, 2).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_parse_submission() {
+        assert_eq!(parse_submission(TOO_HIGH), Some(Submission::TooHigh));
+        assert_eq!(
+            parse_submission("<article><p>That's the right answer!</p></article>"),
+            Some(Submission::Correct)
+        );
+        assert_eq!(
+            parse_submission("<article><p>That's not the right answer. \
+                              Please wait one minute.</p></article>"),
+            Some(Submission::Incorrect)
+        );
+        assert_eq!(
+            parse_submission("<article><p>You gave an answer too recently; you have \
+                              to wait after submitting an answer before trying again. \
+                              You have <span>1m 5s</span> left to wait.</p></article>"),
+            Some(Submission::TooSoon { wait: Some(Duration::from_secs(65)) })
+        );
+        assert_eq!(
+            parse_submission("<article><p>You don't seem to be solving the right \
+                              level. Did you already complete it?</p></article>"),
+            Some(Submission::AlreadySolved)
+        );
+        assert_eq!(parse_submission("<html>Maintenance</html>"), None);
+    }
+
+    /// Judges answers against a fixed one and remembers what it was asked.
+    struct Judge {
+        correct: &'static str,
+        submitted: RefCell<Vec<String>>,
+    }
+
+    impl Fetcher for Judge {
+        fn fetch_input(&self, _year: u16, _day: u8) -> Result<String, FetchError> {
+            panic!("Judge::fetch_input should not be called when submitting")
+        }
+
+        fn submit_answer(
+            &self,
+            _year: u16,
+            _day: u8,
+            _part: Part,
+            answer: &str,
+        ) -> Result<Submission, FetchError> {
+            self.submitted.borrow_mut().push(answer.to_string());
+            Ok(if answer == self.correct { Submission::Correct } else { Submission::TooLow })
+        }
+    }
+
+    #[test]
+    fn test_submit() {
+        let temp = TempDir::new("submit");
+        let root = temp.path();
+        fs::create_dir_all(input::day_dir(root, 1970, 1)).unwrap();
+        let judge = Judge { correct: "10", submitted: RefCell::new(Vec::new()) };
+        let mut results = Vec::new();
+        for answer in ["5", "5", "10", "10"] {
+            results.push(submit(&judge, root, 1970, 1, Part::One, answer).unwrap());
+        }
+        let answers = answers::read_answers(&answers::answers_path(root, 1970, 1));
+        let log = read_log(&submissions_path(root, 1970, 1));
+
+        assert_eq!(results, vec![
+            (Submission::TooLow, false),
+            (Submission::TooLow, true),
+            (Submission::Correct, false),
+            (Submission::Correct, true),
+        ]);
+        assert_eq!(*judge.submitted.borrow(), vec!["5", "10"]);
+        assert_eq!(answers.unwrap().get(Part::One), Some("10"));
+        assert_eq!(log.unwrap().len(), 2);
+    }
+}
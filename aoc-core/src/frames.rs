@@ -0,0 +1,204 @@
+//! Recording the generations of a simulation on a grid, to watch how it
+//! evolves: as a text file with one frame after the other, as a replay in
+//! the terminal, or as a series of PPM or PNG images.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+use crate::grid::Grid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ImageFormat, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "ppm" => Ok(ImageFormat::Ppm),
+            "png" => Ok(ImageFormat::Png),
+            _ => Err(format!("Unknown image format \"{}\"", s)),
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+/// The frames of a simulation, each with the step it was taken at.
+#[derive(Clone, Debug, Default)]
+pub struct FrameRecorder<T> {
+    frames: Vec<(usize, Grid<T>)>,
+}
+
+impl<T: Clone> FrameRecorder<T> {
+    pub fn new() -> FrameRecorder<T> {
+        FrameRecorder { frames: Vec::new() }
+    }
+
+    pub fn record(&mut self, step: usize, grid: &Grid<T>) {
+        self.frames.push((step, grid.clone()));
+    }
+}
+
+impl<T> FrameRecorder<T> {
+    pub fn frames(&self) -> &[(usize, Grid<T>)] {
+        &self.frames
+    }
+
+    /// Writes one image per frame into `dir`, named `frame0000.png` etc.,
+    /// with `scale`x`scale` pixels per cell in the colour given by `color`.
+    /// Returns the paths of the images.
+    pub fn write_images<F>(
+        &self,
+        dir: &Path,
+        format: ImageFormat,
+        scale: usize,
+        color: F,
+    ) -> io::Result<Vec<PathBuf>>
+    where F: Fn(&T) -> [u8; 3] {
+        fs::create_dir_all(dir)?;
+        self.frames.iter().map(|(step, grid)| {
+            let path = dir.join(format!("frame{:04}.{}", step, format.extension()));
+            let mut out = BufWriter::new(File::create(&path)?);
+            write_image(&mut out, grid, format, scale, &color)?;
+            out.flush()?;
+            Ok(path)
+        }).collect()
+    }
+}
+
+impl<T: fmt::Display> FrameRecorder<T> {
+    /// Writes all frames as text, each one headed by its step and followed
+    /// by an empty line.
+    pub fn write_ascii<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for (step, grid) in &self.frames {
+            writeln!(out, "Step {}", step)?;
+            writeln!(out, "{}", grid)?;
+        }
+        Ok(())
+    }
+
+    /// Replays the frames in a terminal, clearing the screen before each
+    /// frame and waiting `delay` after it.
+    pub fn play<W: Write>(&self, out: &mut W, delay: Duration) -> io::Result<()> {
+        for (step, grid) in &self.frames {
+            writeln!(out, "\x1b[2J\x1b[H{}Step {}", grid, step)?;
+            out.flush()?;
+            thread::sleep(delay);
+        }
+        Ok(())
+    }
+}
+
+/// Renders a grid as an image in the given format.
+pub fn write_image<T, F, W>(
+    out: &mut W,
+    grid: &Grid<T>,
+    format: ImageFormat,
+    scale: usize,
+    color: F,
+) -> io::Result<()>
+where F: Fn(&T) -> [u8; 3], W: Write {
+    let (width, height) = (grid.width() * scale, grid.height() * scale);
+    let mut rgb: Vec<u8> = Vec::with_capacity(width * height * 3);
+    for row in grid.rows() {
+        let line: Vec<u8> = row.iter()
+            .flat_map(|cell| std::iter::repeat_n(color(cell), scale))
+            .flatten()
+            .collect();
+        for _ in 0..scale {
+            rgb.extend(&line);
+        }
+    }
+    match format {
+        ImageFormat::Ppm => {
+            write!(out, "P6\n{} {}\n255\n", width, height)?;
+            out.write_all(&rgb)
+        },
+        ImageFormat::Png => {
+            let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder.write_header()
+                .and_then(|mut writer| writer.write_image_data(&rgb))
+                .map_err(io::Error::other)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+
+    fn recorder() -> FrameRecorder<char> {
+        let mut recorder = FrameRecorder::new();
+        recorder.record(0, &Grid::from_char_map("#.\n..\n", |c| c).unwrap());
+        recorder.record(1, &Grid::from_char_map(".#\n..\n", |c| c).unwrap());
+        recorder
+    }
+
+    fn color(c: &char) -> [u8; 3] {
+        if *c == '#' { [255, 0, 0] } else { [0, 0, 0] }
+    }
+
+    #[test]
+    fn test_write_ascii() {
+        let mut out: Vec<u8> = Vec::new();
+        recorder().write_ascii(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Step 0\n#.\n..\n\nStep 1\n.#\n..\n\n");
+
+        let mut out: Vec<u8> = Vec::new();
+        recorder().play(&mut out, Duration::ZERO).unwrap();
+        assert!(String::from_utf8(out).unwrap().ends_with("\x1b[2J\x1b[H.#\n..\nStep 1\n"));
+    }
+
+    #[test]
+    fn test_write_image() {
+        let recorder = recorder();
+        let (_, grid) = &recorder.frames()[1];
+        let mut ppm: Vec<u8> = Vec::new();
+        write_image(&mut ppm, grid, ImageFormat::Ppm, 2, color).unwrap();
+        let header = b"P6\n4 4\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        let pixels = &ppm[header.len()..];
+        assert_eq!(pixels.len(), 4 * 4 * 3);
+        // The second row of pixels is the first row of cells again.
+        assert_eq!(&pixels[12..24], &[0, 0, 0, 0, 0, 0, 255, 0, 0, 255, 0, 0]);
+
+        let mut png: Vec<u8> = Vec::new();
+        write_image(&mut png, grid, ImageFormat::Png, 1, color).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!("PNG".parse(), Ok(ImageFormat::Png));
+    }
+
+    #[test]
+    fn test_write_images() {
+        let temp = TempDir::new("frames");
+        let dir = temp.path();
+        let paths = recorder().write_images(dir, ImageFormat::Ppm, 1, color).unwrap();
+        let first = fs::read(&paths[0]).unwrap();
+        assert_eq!(paths, vec![dir.join("frame0000.ppm"), dir.join("frame0001.ppm")]);
+        assert_eq!(first, b"P6\n2 2\n255\n\xff\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00");
+    }
+}
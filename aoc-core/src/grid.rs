@@ -0,0 +1,300 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use crate::error::AoCError;
+use crate::parse;
+
+/// A position in a grid as `(x, y)`, with `(0, 0)` in the top left.
+pub type Pos = (usize, usize);
+
+/// The steps to the four orthogonal neighbours, as `(dx, dy)`.
+pub const DIRECTIONS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// The steps to the eight neighbours including the diagonal ones, as
+/// `(dx, dy)`, clockwise from the top left.
+pub const DIRECTIONS_8: [(isize, isize); 8] = [
+    (-1, -1), (0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0),
+];
+
+/// A dense, rectangular 2D grid stored in row-major order.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+        assert_eq!(
+            cells.len(), width * height,
+            "{} cells don't fit a {}x{} grid", cells.len(), width, height
+        );
+        Grid { cells, width, height }
+    }
+
+    /// Parses a map with one char per cell, such as
+    /// ```text
+    /// L.LL
+    /// LLL#
+    /// ```
+    /// Returns `None` if the rows differ in length.
+    pub fn from_char_map<F>(map: &str, mut f: F) -> Option<Grid<T>>
+    where F: FnMut(char) -> T {
+        let mut cells: Vec<T> = Vec::new();
+        let mut width: Option<usize> = None;
+        let mut height: usize = 0;
+        for line in map.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            let row_len = line.chars().count();
+            if *width.get_or_insert(row_len) != row_len { return None; }
+            cells.extend(line.chars().map(&mut f));
+            height += 1;
+        }
+        Some(Grid { cells, width: width.unwrap_or(0), height })
+    }
+
+    /// Like `from_char_map`, but for puzzle inputs: `f` returns `None` for
+    /// chars that are not allowed, and unknown chars, rows of different
+    /// lengths and empty maps are reported as parse errors.
+    pub fn parse_char_map<F>(input: &str, mut f: F) -> Result<Grid<T>, AoCError>
+    where F: FnMut(char) -> Option<T> {
+        let mut cells: Vec<T> = Vec::new();
+        let mut width: Option<usize> = None;
+        let mut height: usize = 0;
+        for (i, line) in parse::lines(input) {
+            let line = line.trim();
+            for (j, c) in line.chars().enumerate() {
+                cells.push(f(c).ok_or_else(|| {
+                    AoCError::parse(i, j + 1, format!("Unexpected character '{}'", c))
+                })?);
+            }
+            let row_len = line.chars().count();
+            let width = *width.get_or_insert(row_len);
+            if row_len != width {
+                return Err(AoCError::parse(i, 1, format!(
+                    "Expected {} cells in the row, but found {}", width, row_len
+                )));
+            }
+            height += 1;
+        }
+        match width {
+            Some(width) if width > 0 => Ok(Grid { cells, width, height }),
+            _ => Err(AoCError::malformed("The map is empty")),
+        }
+    }
+
+    pub fn width(&self) -> usize { self.width }
+
+    pub fn height(&self) -> usize { self.height }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x >= self.width || y >= self.height { return None; }
+        self.cells.get(y * self.width + x)
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x >= self.width || y >= self.height { return None; }
+        self.cells.get_mut(y * self.width + x)
+    }
+
+    /// Accesses the grid as if it repeated indefinitely in all directions.
+    /// Panics if the grid is empty.
+    pub fn get_wrapping(&self, x: isize, y: isize) -> &T {
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+        &self.cells[y * self.width + x]
+    }
+
+    /// The position one step from `pos` in direction `(dx, dy)`, if it is
+    /// inside the grid.
+    pub fn step(&self, (x, y): Pos, (dx, dy): (isize, isize)) -> Option<Pos> {
+        let x = x.checked_add_signed(dx).filter(|&x| x < self.width)?;
+        let y = y.checked_add_signed(dy).filter(|&y| y < self.height)?;
+        Some((x, y))
+    }
+
+    /// The position `(dx, dy)` away from `pos` if the grid wrapped around
+    /// at its edges like a torus.
+    pub fn step_wrapping(&self, (x, y): Pos, (dx, dy): (isize, isize)) -> Pos {
+        (
+            (x as isize + dx).rem_euclid(self.width as isize) as usize,
+            (y as isize + dy).rem_euclid(self.height as isize) as usize,
+        )
+    }
+
+    /// The positions of the up to four orthogonal neighbours of `pos`.
+    pub fn neighbors_4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRECTIONS_4.iter().filter_map(move |&dir| self.step(pos, dir))
+    }
+
+    /// The positions of the up to eight neighbours of `pos`, including the
+    /// diagonal ones.
+    pub fn neighbors_8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRECTIONS_8.iter().filter_map(move |&dir| self.step(pos, dir))
+    }
+
+    /// The positions on the way from `pos` (exclusive) in direction
+    /// `(dx, dy)` to the edge of the grid.
+    pub fn ray(&self, pos: Pos, dir: (isize, isize)) -> impl Iterator<Item = Pos> + '_ {
+        std::iter::successors(self.step(pos, dir), move |&pos| self.step(pos, dir))
+    }
+
+    /// All positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.width * self.height).map(move |i| (i % width, i / width))
+    }
+
+    /// Iterates over all cells in row-major order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    /// Iterates over all cells along with their positions.
+    pub fn enumerate(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column {} out of bounds for width {}", x, self.width);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// A grid of the same size with `f` applied to each cell.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid { cells: self.cells.iter().map(f).collect(), width: self.width, height: self.height }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Grid<T> {
+        Grid { cells: vec![value; width * height], width, height }
+    }
+
+    /// The grid mirrored along its main diagonal, so that rows become
+    /// columns.
+    pub fn transpose(&self) -> Grid<T> {
+        let cells = self.columns().flatten().cloned().collect();
+        Grid { cells, width: self.height, height: self.width }
+    }
+}
+
+/// Panics if the position is outside the grid.
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): Pos) -> &T {
+        self.get(x, y).unwrap_or_else(|| panic!(
+            "({}, {}) out of bounds for a {}x{} grid", x, y, self.width, self.height
+        ))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, (x, y): Pos) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(x, y).unwrap_or_else(|| panic!(
+            "({}, {}) out of bounds for a {}x{} grid", x, y, width, height
+        ))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_char_map() {
+        let grid = Grid::from_char_map("#.\n.#\n#.\n", |c| c == '#').unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert_eq!(grid.get(0, 2), Some(&true));
+        assert_eq!(grid.get(1, 2), Some(&false));
+        assert_eq!(grid.get(2, 0), None);
+        assert!(Grid::from_char_map("##\n#", |c| c).is_none());
+    }
+
+    #[test]
+    fn test_parse_char_map() {
+        let digit = |c: char| c.to_digit(10);
+        let grid = Grid::parse_char_map("12\n34\n", digit).unwrap();
+        assert_eq!(grid[(1, 0)], 2);
+        assert_eq!(
+            Grid::parse_char_map("12\n3x\n", digit),
+            Err(AoCError::parse(2, 2, "Unexpected character 'x'"))
+        );
+        assert_eq!(
+            Grid::parse_char_map("12\n\n345\n", digit),
+            Err(AoCError::parse(3, 1, "Expected 2 cells in the row, but found 3"))
+        );
+        assert_eq!(Grid::parse_char_map("\n", digit), Err(AoCError::malformed("The map is empty")));
+    }
+
+    #[test]
+    fn test_access() {
+        let grid = Grid::new(3, 2, (0..6).collect());
+        assert_eq!(grid[(2, 1)], 5);
+        assert_eq!(*grid.get_wrapping(-1, 3), 5);
+        assert_eq!(*grid.get_wrapping(7, -2), 1);
+        assert_eq!(grid.step((0, 0), (-1, 0)), None);
+        assert_eq!(grid.step((0, 0), (1, 1)), Some((1, 1)));
+        assert_eq!(grid.step((2, 1), (1, 0)), None);
+        assert_eq!(grid.step_wrapping((2, 1), (1, 0)), (0, 1));
+        assert_eq!(grid.step_wrapping((0, 0), (-4, -3)), (2, 1));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::filled(3, 3, 0);
+        assert_eq!(grid.neighbors_4((1, 1)).count(), 4);
+        assert_eq!(grid.neighbors_8((1, 1)).count(), 8);
+        assert_eq!(grid.neighbors_4((0, 0)).collect::<Vec<Pos>>(), vec![(1, 0), (0, 1)]);
+        assert_eq!(
+            grid.neighbors_8((2, 0)).collect::<Vec<Pos>>(),
+            vec![(2, 1), (1, 1), (1, 0)]
+        );
+        assert_eq!(grid.ray((0, 0), (1, 1)).collect::<Vec<Pos>>(), vec![(1, 1), (2, 2)]);
+        assert_eq!(grid.ray((0, 1), (-1, 0)).count(), 0);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = Grid::new(3, 2, (0..6).collect());
+        assert_eq!(grid.rows().collect::<Vec<&[i32]>>(), vec![&[0, 1, 2], &[3, 4, 5]]);
+        assert_eq!(grid.column(1).collect::<Vec<&i32>>(), vec![&1, &4]);
+        let transposed = grid.transpose();
+        assert_eq!((transposed.width(), transposed.height()), (2, 3));
+        assert_eq!(transposed.row(2), &[2, 5]);
+        assert_eq!(transposed.transpose(), grid);
+        assert_eq!(grid.enumerate().nth(4), Some(((1, 1), &4)));
+    }
+
+    #[test]
+    fn test_display() {
+        let mut grid = Grid::filled(3, 2, '.');
+        *grid.get_mut(1, 1).unwrap() = '#';
+        assert_eq!(grid.to_string(), "...\n.#.\n");
+    }
+}
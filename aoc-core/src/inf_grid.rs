@@ -0,0 +1,219 @@
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use crate::grid::Grid;
+use crate::life::LifeRule;
+
+/// An unbounded, sparse grid of cells that are either active or inactive,
+/// in any number of dimensions `D`. Only the active cells are stored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InfGrid<const D: usize> {
+    active: HashSet<[i64; D]>,
+}
+
+impl<const D: usize> InfGrid<D> {
+    pub fn new() -> InfGrid<D> {
+        InfGrid { active: HashSet::new() }
+    }
+
+    /// Places a 2D map in the plane spanned by the first two dimensions, at
+    /// 0 in all others, with x going along the first dimension.
+    pub fn from_grid(grid: &Grid<bool>) -> InfGrid<D> {
+        assert!(D >= 2, "A {}D grid has no room for a 2D map", D);
+        let mut inf_grid = InfGrid::new();
+        for ((x, y), &active) in grid.enumerate() {
+            let mut pos = [0; D];
+            pos[0] = x as i64;
+            pos[1] = y as i64;
+            inf_grid.set(pos, active);
+        }
+        inf_grid
+    }
+
+    pub fn is_active(&self, pos: &[i64; D]) -> bool {
+        self.active.contains(pos)
+    }
+
+    pub fn set(&mut self, pos: [i64; D], active: bool) {
+        if active {
+            self.active.insert(pos);
+        } else {
+            self.active.remove(&pos);
+        }
+    }
+
+    pub fn count_active(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Iterates over the active cells in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &[i64; D]> {
+        self.active.iter()
+    }
+
+    /// The offsets to the `3^D - 1` neighbours of a cell, i.e. all cells
+    /// that differ by at most 1 in each dimension.
+    pub fn neighbor_offsets() -> Vec<[i64; D]> {
+        let mut offsets: Vec<[i64; D]> = vec![[0; D]];
+        for dim in 0..D {
+            offsets = offsets.into_iter()
+                .flat_map(|offset| (-1..=1).map(move |d| {
+                    let mut offset = offset;
+                    offset[dim] = d;
+                    offset
+                }))
+                .collect();
+        }
+        offsets.retain(|offset| offset.iter().any(|&d| d != 0));
+        offsets
+    }
+
+    /// Counts the active neighbours of every cell that has at least one.
+    /// Only the active cells are visited.
+    pub fn count_neighbors(&self) -> HashMap<[i64; D], usize> {
+        let offsets = InfGrid::<D>::neighbor_offsets();
+        let mut counts: HashMap<[i64; D], usize> =
+            HashMap::with_capacity(self.active.len() * offsets.len());
+        for cell in &self.active {
+            add_neighbors(&mut counts, cell, &offsets);
+        }
+        counts
+    }
+
+    /// Like `count_neighbors`, but every thread counts the neighbours of a
+    /// share of the active cells, and the counts are added up afterwards.
+    #[cfg(feature = "parallel")]
+    pub fn par_count_neighbors(&self) -> HashMap<[i64; D], usize> {
+        let offsets = InfGrid::<D>::neighbor_offsets();
+        self.active.par_iter()
+            .fold(HashMap::new, |mut counts, cell| {
+                add_neighbors(&mut counts, cell, &offsets);
+                counts
+            })
+            .reduce(HashMap::new, |mut counts, mut other| {
+                if counts.len() < other.len() {
+                    std::mem::swap(&mut counts, &mut other);
+                }
+                for (pos, n) in other {
+                    *counts.entry(pos).or_insert(0) += n;
+                }
+                counts
+            })
+    }
+
+    /// Computes the next generation, where `rule(active, neighbors)` tells
+    /// whether a cell is active afterwards. Only active cells and their
+    /// neighbours are considered, so inactive cells without active
+    /// neighbours always stay inactive.
+    ///
+    /// With the `parallel` feature, the cells are evaluated on several
+    /// threads.
+    pub fn step_with<F>(&self, rule: F) -> InfGrid<D>
+    where F: Fn(bool, usize) -> bool + Sync {
+        #[cfg(feature = "parallel")]
+        return self.par_step_with(rule);
+        #[cfg(not(feature = "parallel"))]
+        return self.serial_step_with(rule);
+    }
+
+    #[cfg(any(test, not(feature = "parallel")))]
+    fn serial_step_with<F>(&self, rule: F) -> InfGrid<D>
+    where F: Fn(bool, usize) -> bool {
+        let counts = self.count_neighbors();
+        let mut active: HashSet<[i64; D]> = counts.iter()
+            .filter(|&(pos, &n)| rule(self.is_active(pos), n))
+            .map(|(pos, _)| *pos)
+            .collect();
+        // Active cells without active neighbours don't show up in `counts`.
+        active.extend(self.active.iter()
+            .filter(|pos| !counts.contains_key(*pos) && rule(true, 0)));
+        InfGrid { active }
+    }
+
+    #[cfg(feature = "parallel")]
+    fn par_step_with<F>(&self, rule: F) -> InfGrid<D>
+    where F: Fn(bool, usize) -> bool + Sync {
+        let counts = self.par_count_neighbors();
+        let mut active: HashSet<[i64; D]> = counts.par_iter()
+            .filter(|&(pos, &n)| rule(self.is_active(pos), n))
+            .map(|(pos, _)| *pos)
+            .collect();
+        active.par_extend(self.active.par_iter()
+            .filter(|pos| !counts.contains_key(*pos) && rule(true, 0)));
+        InfGrid { active }
+    }
+
+    /// The next generation under a life-like rule. Births without active
+    /// neighbours (`B0`) are ignored, since they would fill the infinite
+    /// grid.
+    pub fn step(&self, rule: &LifeRule) -> InfGrid<D> {
+        self.step_with(|active, n| rule.next_state(active, n))
+    }
+}
+
+fn add_neighbors<const D: usize>(
+    counts: &mut HashMap<[i64; D], usize>,
+    cell: &[i64; D],
+    offsets: &[[i64; D]],
+) {
+    for offset in offsets {
+        let mut neighbor = *cell;
+        for (coordinate, d) in neighbor.iter_mut().zip(offset) {
+            *coordinate += d;
+        }
+        *counts.entry(neighbor).or_insert(0) += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbor_offsets() {
+        assert_eq!(InfGrid::<1>::neighbor_offsets(), vec![[-1], [1]]);
+        assert_eq!(InfGrid::<2>::neighbor_offsets().len(), 8);
+        assert_eq!(InfGrid::<4>::neighbor_offsets().len(), 80);
+    }
+
+    #[test]
+    fn test_step() {
+        let conway = LifeRule::conway();
+        let blinker = Grid::from_char_map("...\n###\n...\n", |c| c == '#').unwrap();
+        let grid = InfGrid::<2>::from_grid(&blinker);
+        let next = grid.step(&conway);
+        assert_eq!(next.count_active(), 3);
+        assert!(next.is_active(&[1, 0]) && next.is_active(&[1, 2]));
+        assert_eq!(next.step(&conway), grid);
+
+        let glider = Grid::from_char_map(".#.\n..#\n###\n", |c| c == '#').unwrap();
+        let mut grid = InfGrid::<2>::from_grid(&glider);
+        for _ in 0..4 {
+            grid = grid.step(&conway);
+        }
+        let moved: HashSet<[i64; 2]> = InfGrid::<2>::from_grid(&glider).iter()
+            .map(|&[x, y]| [x + 1, y + 1])
+            .collect();
+        assert_eq!(grid.iter().copied().collect::<HashSet<_>>(), moved);
+
+        let mut lonely = InfGrid::<3>::new();
+        lonely.set([5, -5, 0], true);
+        assert_eq!(lonely.step_with(|active, _| active).count_active(), 1);
+        assert_eq!(lonely.step(&conway).count_active(), 0);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel() {
+        let glider = Grid::from_char_map(".#.\n..#\n###\n", |c| c == '#').unwrap();
+        for rule in [LifeRule::conway(), "B36/S23".parse().unwrap(), "B2/S".parse().unwrap()] {
+            let mut grid = InfGrid::<4>::from_grid(&glider);
+            for _ in 0..4 {
+                let next = grid.serial_step_with(|active, n| rule.next_state(active, n));
+                assert_eq!(grid.par_count_neighbors(), grid.count_neighbors());
+                assert_eq!(grid.par_step_with(|active, n| rule.next_state(active, n)), next);
+                grid = next;
+            }
+        }
+    }
+}
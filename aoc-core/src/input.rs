@@ -0,0 +1,98 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Where a puzzle input is read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    /// Interprets a command line argument, where "-" stands for stdin.
+    pub fn from_arg(arg: &str) -> InputSource {
+        if arg == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::File(PathBuf::from(arg))
+        }
+    }
+
+    /// Reads the whole input; see `read_input`.
+    pub fn read(&self) -> io::Result<String> {
+        match self {
+            InputSource::File(path) => read_input(path),
+            InputSource::Stdin => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input)?;
+                Ok(normalize_line_endings(input))
+            }
+        }
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputSource::File(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "<stdin>"),
+        }
+    }
+}
+
+/// The directory of a day's puzzle files, e.g. `<root>/2020/05`.
+pub fn day_dir(root: &Path, year: u16, day: u8) -> PathBuf {
+    root.join(year.to_string()).join(format!("{:02}", day))
+}
+
+/// The location of a day's puzzle input, e.g. `<root>/2020/05/input`.
+pub fn input_path(root: &Path, year: u16, day: u8) -> PathBuf {
+    day_dir(root, year, day).join("input")
+}
+
+/// The location of a named example input that is stored next to the real
+/// one, e.g. `<root>/2020/05/example1.txt` for the name "1".
+pub fn example_path(root: &Path, year: u16, day: u8, name: &str) -> PathBuf {
+    day_dir(root, year, day).join(format!("example{}.txt", name))
+}
+
+/// Reads a puzzle input file.
+///
+/// Windows line endings are normalized so that the solutions only ever
+/// have to split on `'\n'`.
+pub fn read_input<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    fs::read_to_string(path).map(normalize_line_endings)
+}
+
+fn normalize_line_endings(input: String) -> String {
+    if input.contains('\r') {
+        return input.replace("\r\n", "\n");
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths() {
+        let root = Path::new("/aoc");
+        assert_eq!(input_path(root, 2020, 5), Path::new("/aoc/2020/05/input"));
+        assert_eq!(
+            example_path(root, 2021, 12, "2"),
+            Path::new("/aoc/2021/12/example2.txt")
+        );
+    }
+
+    #[test]
+    fn test_from_arg() {
+        assert_eq!(InputSource::from_arg("-"), InputSource::Stdin);
+        assert_eq!(
+            InputSource::from_arg("in.txt"),
+            InputSource::File(PathBuf::from("in.txt"))
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Shared infrastructure for the Advent of Code solutions of all years:
+//! the `Solution` trait and registry, input loading, parsing helpers, grids
+//! and the runner used by the `aoc` binary.
+
+pub mod answers;
+pub mod bench;
+pub mod debug;
+mod error;
+pub mod examples;
+pub mod fetch;
+pub mod frames;
+pub mod grid;
+pub mod inf_grid;
+pub mod input;
+pub mod life;
+pub mod parse;
+pub mod provider;
+pub mod runner;
+pub mod scaffold;
+mod solution;
+#[cfg(test)]
+mod test_dir;
+pub mod verify;
+
+pub use error::AoCError;
+pub use grid::Grid;
+pub use solution::{Answer, DynSolution, Params, Part, Registry, Solution};
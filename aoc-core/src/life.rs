@@ -0,0 +1,121 @@
+//! Rules of life-like cellular automata in the usual B/S notation, e.g.
+//! `B3/S23` for Conway's Game of Life: inactive cells with 3 active
+//! neighbours become active (birth), and active cells with 2 or 3 active
+//! neighbours stay active (survival).
+//!
+//! In higher dimensions, cells can have more than 9 neighbours, so the
+//! counts may also be separated by commas, as in `B3/S2,3,10`.
+
+use std::fmt;
+use std::str::FromStr;
+use crate::error::AoCError;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LifeRule {
+    birth: Vec<usize>,
+    survival: Vec<usize>,
+}
+
+impl LifeRule {
+    pub fn new(birth: &[usize], survival: &[usize]) -> LifeRule {
+        let sorted = |counts: &[usize]| {
+            let mut counts = counts.to_vec();
+            counts.sort_unstable();
+            counts.dedup();
+            counts
+        };
+        LifeRule { birth: sorted(birth), survival: sorted(survival) }
+    }
+
+    /// `B3/S23`
+    pub fn conway() -> LifeRule {
+        LifeRule::new(&[3], &[2, 3])
+    }
+
+    /// Whether a cell is active in the next generation.
+    pub fn next_state(&self, active: bool, neighbors: usize) -> bool {
+        let counts = if active { &self.survival } else { &self.birth };
+        counts.binary_search(&neighbors).is_ok()
+    }
+}
+
+fn parse_counts(s: &str) -> Option<Vec<usize>> {
+    if s.contains(',') {
+        s.split(',').map(|n| n.trim().parse().ok()).collect()
+    } else {
+        s.chars().map(|c| c.to_digit(10).map(|n| n as usize)).collect()
+    }
+}
+
+impl FromStr for LifeRule {
+    type Err = AoCError;
+
+    /// Parses e.g. `B3/S23`. The parts may come in either order.
+    fn from_str(s: &str) -> Result<LifeRule, AoCError> {
+        let invalid = || AoCError::malformed(format!(
+            "Invalid rule \"{}\", expected e.g. \"B3/S23\"", s
+        ));
+        let (first, second) = s.trim().split_once('/').ok_or_else(invalid)?;
+        let mut birth: Option<Vec<usize>> = None;
+        let mut survival: Option<Vec<usize>> = None;
+        for part in [first, second] {
+            let mut chars = part.chars();
+            let counts = match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') if birth.is_none() => &mut birth,
+                Some('S') if survival.is_none() => &mut survival,
+                _ => return Err(invalid()),
+            };
+            *counts = Some(parse_counts(chars.as_str()).ok_or_else(invalid)?);
+        }
+        Ok(LifeRule::new(&birth.unwrap(), &survival.unwrap()))
+    }
+}
+
+impl fmt::Display for LifeRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let write_counts = |f: &mut fmt::Formatter, counts: &[usize]| {
+            let separator = if counts.iter().any(|&n| n > 9) { "," } else { "" };
+            let counts: Vec<String> = counts.iter().map(|n| n.to_string()).collect();
+            write!(f, "{}", counts.join(separator))
+        };
+        write!(f, "B")?;
+        write_counts(f, &self.birth)?;
+        write!(f, "/S")?;
+        write_counts(f, &self.survival)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let conway: LifeRule = "B3/S23".parse().unwrap();
+        assert_eq!(conway, LifeRule::conway());
+        assert_eq!("s32/b3".parse::<LifeRule>(), Ok(LifeRule::conway()));
+        assert_eq!("B0/S".parse::<LifeRule>(), Ok(LifeRule::new(&[0], &[])));
+        assert_eq!(
+            "B3/S2,3,12".parse::<LifeRule>(),
+            Ok(LifeRule::new(&[3], &[2, 3, 12]))
+        );
+        assert!("B3".parse::<LifeRule>().is_err());
+        assert!("B3/B3".parse::<LifeRule>().is_err());
+        assert!("B3/S2x".parse::<LifeRule>().is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(LifeRule::new(&[6, 3], &[3, 2]).to_string(), "B36/S23");
+        assert_eq!(LifeRule::new(&[3], &[12, 2]).to_string(), "B3/S2,12");
+    }
+
+    #[test]
+    fn test_next_state() {
+        let conway = LifeRule::conway();
+        assert!(conway.next_state(false, 3));
+        assert!(!conway.next_state(false, 2));
+        assert!(conway.next_state(true, 2));
+        assert!(!conway.next_state(true, 4));
+    }
+}
@@ -0,0 +1,116 @@
+//! Helpers for the input formats that come up again and again.
+
+use std::fmt::Display;
+use std::str::FromStr;
+use crate::error::AoCError;
+
+/// Iterates over the non-empty lines of the input along with their 1-based
+/// line numbers, for error messages.
+pub fn lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input.split('\n')
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty())
+}
+
+/// Parses a single value that was found at the given 1-based line and
+/// column.
+pub fn value<T>(s: &str, line: usize, column: usize) -> Result<T, AoCError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    s.parse::<T>().map_err(|e| AoCError::parse(
+        line, column, format!("Invalid value \"{}\": {}", s, e)
+    ))
+}
+
+/// Parses one number per line, skipping empty lines.
+pub fn numbers<T>(input: &str) -> Result<Vec<T>, AoCError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    lines(input)
+        .map(|(i, line)| {
+            let column = line.len() - line.trim_start().len() + 1;
+            value::<T>(line.trim(), i, column)
+        })
+        .collect()
+}
+
+/// Parses a single line of comma-separated values, e.g. "0,3,6", that is
+/// line `line_number` of the input.
+pub fn comma_separated<T>(line: &str, line_number: usize) -> Result<Vec<T>, AoCError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let mut column = 1;
+    let mut values = Vec::new();
+    for s in line.trim_end().split(',') {
+        let offset = s.len() - s.trim_start().len();
+        values.push(value::<T>(s.trim(), line_number, column + offset)?);
+        column += s.chars().count() + 1;
+    }
+    Ok(values)
+}
+
+/// Splits the input into blocks that are separated by empty lines.
+pub fn blocks(input: &str) -> impl Iterator<Item = &str> {
+    input.split("\n\n")
+        .map(|block| block.trim_matches('\n'))
+        .filter(|block| !block.trim().is_empty())
+}
+
+/// Like `blocks`, but keeps the 1-based line numbers of the lines in each
+/// block, for error messages.
+pub fn numbered_blocks(input: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut blocks: Vec<Vec<(usize, &str)>> = vec![Vec::new()];
+    for (i, line) in input.split('\n').enumerate() {
+        let line = line.trim_end();
+        if !line.is_empty() {
+            blocks.last_mut().unwrap().push((i + 1, line));
+        } else if !blocks.last().unwrap().is_empty() {
+            blocks.push(Vec::new());
+        }
+    }
+    blocks.retain(|block| !block.is_empty());
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(numbers::<u32>("1\n2\n\n3\n"), Ok(vec![1, 2, 3]));
+        assert_eq!(
+            numbers::<i64>("-1\nx\n2"),
+            Err(AoCError::parse(
+                2, 1, "Invalid value \"x\": invalid digit found in string"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_comma_separated() {
+        assert_eq!(comma_separated::<u32>("0,3,6\n", 1), Ok(vec![0, 3, 6]));
+        assert!(matches!(
+            comma_separated::<u32>("0, a", 4),
+            Err(AoCError::Parse { position: Some((4, 4)), .. })
+        ));
+    }
+
+    #[test]
+    fn test_blocks() {
+        let input = "abc\n\na\nb\nc\n\n\nab\n";
+        assert_eq!(blocks(input).collect::<Vec<_>>(), vec!["abc", "a\nb\nc", "ab"]);
+        assert_eq!(numbered_blocks(input), vec![
+            vec![(1, "abc")],
+            vec![(3, "a"), (4, "b"), (5, "c")],
+            vec![(8, "ab")],
+        ]);
+    }
+}
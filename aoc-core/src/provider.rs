@@ -0,0 +1,112 @@
+//! Where the runner gets the puzzle inputs of the registered days from.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::fetch::{FetchError, Fetcher};
+use crate::input::{self, InputSource};
+use crate::runner::RunError;
+
+pub trait InputProvider {
+    /// The puzzle input of a day.
+    fn input(&self, year: u16, day: u8) -> Result<String, RunError>;
+}
+
+/// Reads the inputs from `<root>/<year>/<NN>/input`.
+pub struct LocalInputs {
+    root: PathBuf,
+}
+
+impl LocalInputs {
+    pub fn new(root: &Path) -> LocalInputs {
+        LocalInputs { root: root.to_path_buf() }
+    }
+}
+
+impl InputProvider for LocalInputs {
+    fn input(&self, year: u16, day: u8) -> Result<String, RunError> {
+        let source = InputSource::File(input::input_path(&self.root, year, day));
+        source.read().map_err(|e| RunError::Input { input: source, source: e })
+    }
+}
+
+/// Like `LocalInputs`, but downloads missing inputs and stores them where
+/// `LocalInputs` would look for them, so each input is only fetched once.
+/// An empty input file counts as missing, like the one `aoc new` creates.
+pub struct CachedInputs<F: Fetcher> {
+    local: LocalInputs,
+    fetcher: F,
+}
+
+impl<F: Fetcher> CachedInputs<F> {
+    pub fn new(root: &Path, fetcher: F) -> CachedInputs<F> {
+        CachedInputs { local: LocalInputs::new(root), fetcher }
+    }
+}
+
+impl<F: Fetcher> InputProvider for CachedInputs<F> {
+    fn input(&self, year: u16, day: u8) -> Result<String, RunError> {
+        let path = input::input_path(&self.local.root, year, day);
+        if fs::metadata(&path).is_ok_and(|metadata| metadata.len() > 0) {
+            return self.local.input(year, day);
+        }
+        let fetched = self.fetcher.fetch_input(year, day)
+            .map_err(|source| RunError::Fetch { year, day, source })?;
+        let write_error = |source| RunError::Fetch {
+            year, day, source: FetchError::Write { path: path.clone(), source }
+        };
+        fs::create_dir_all(input::day_dir(&self.local.root, year, day)).map_err(write_error)?;
+        fs::write(&path, &fetched).map_err(write_error)?;
+        Ok(fetched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use crate::fetch::Submission;
+    use crate::solution::Part;
+    use crate::test_dir::TempDir;
+
+    struct CountingFetcher {
+        fetched: Cell<usize>,
+    }
+
+    impl Fetcher for CountingFetcher {
+        fn fetch_input(&self, year: u16, day: u8) -> Result<String, FetchError> {
+            self.fetched.set(self.fetched.get() + 1);
+            Ok(format!("{} {}\n", year, day))
+        }
+
+        fn submit_answer(
+            &self,
+            _year: u16,
+            _day: u8,
+            _part: Part,
+            _answer: &str,
+        ) -> Result<Submission, FetchError> {
+            panic!("CountingFetcher::submit_answer should not be called by the input cache")
+        }
+    }
+
+    #[test]
+    fn test_cached_inputs() {
+        let temp = TempDir::new("provider");
+        let root = temp.path();
+        let inputs = CachedInputs::new(root, CountingFetcher { fetched: Cell::new(0) });
+        let first = inputs.input(1970, 3).unwrap();
+        let second = inputs.input(1970, 3).unwrap();
+        let local = LocalInputs::new(root).input(1970, 3).unwrap();
+        let missing = LocalInputs::new(root).input(1970, 4);
+        fs::create_dir_all(input::day_dir(root, 1970, 5)).unwrap();
+        fs::write(input::input_path(root, 1970, 5), "").unwrap();
+        let scaffolded = inputs.input(1970, 5).unwrap();
+        let stored = fs::read_to_string(input::input_path(root, 1970, 5)).unwrap();
+
+        assert_eq!((first.as_str(), second.as_str()), ("1970 3\n", "1970 3\n"));
+        assert_eq!(local, "1970 3\n");
+        assert!(matches!(missing, Err(RunError::Input { .. })));
+        assert_eq!((scaffolded.as_str(), stored.as_str()), ("1970 5\n", "1970 5\n"));
+        assert_eq!(inputs.fetcher.fetched.get(), 2);
+    }
+}
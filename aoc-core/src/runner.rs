@@ -0,0 +1,393 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use serde::{Serialize, Serializer};
+use crate::error::AoCError;
+use crate::fetch::FetchError;
+use crate::input::InputSource;
+use crate::provider::InputProvider;
+use crate::solution::{Answer, DynSolution, Params, Part, Registry};
+
+#[derive(Debug)]
+pub enum RunError {
+    UnknownDay { year: u16, day: u8 },
+    Input { input: InputSource, source: io::Error },
+    /// Downloading a missing input failed.
+    Fetch { year: u16, day: u8, source: FetchError },
+    /// Parsing the input (`part` is `None`) or solving a part failed.
+    Solve { year: u16, day: u8, part: Option<Part>, source: AoCError },
+}
+
+impl RunError {
+    /// The exit code of the `aoc` binary for this error, so that scripts can
+    /// tell a broken input from a missing one.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RunError::UnknownDay { .. } => 2,
+            RunError::Input { .. } | RunError::Fetch { .. } => 3,
+            RunError::Solve { source: AoCError::Parse { .. }, .. } => 4,
+            RunError::Solve { source: AoCError::NoSolution(_), .. } => 5,
+        }
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::UnknownDay { year, day } => write!(
+                f, "No solution for {}, day {}", year, day
+            ),
+            RunError::Input { input, source } => write!(
+                f, "Could not read \"{}\": {}", input, source
+            ),
+            RunError::Fetch { year, day, source } => write!(
+                f, "Could not fetch the input of {} day {}: {}", year, day, source
+            ),
+            RunError::Solve { year, day, part: None, source } => write!(
+                f, "{} day {}: {}", year, day, source
+            ),
+            RunError::Solve { year, day, part: Some(part), source } => write!(
+                f, "{} day {} part {}: {}", year, day, part, source
+            ),
+        }
+    }
+}
+
+impl Error for RunError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RunError::Input { source, .. } => Some(source),
+            RunError::Fetch { source, .. } => Some(source),
+            RunError::Solve { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// The answer to one part along with the time it took to compute it.
+#[derive(Clone, Debug, Serialize)]
+pub struct PartResult {
+    pub year: u16,
+    pub day: u8,
+    pub part: Part,
+    pub answer: Answer,
+    #[serde(rename = "elapsed_ms", serialize_with = "serialize_millis")]
+    pub elapsed: Duration,
+}
+
+/// The results of all parts that were run for one day.
+#[derive(Clone, Debug)]
+pub struct DayResult {
+    pub year: u16,
+    pub day: u8,
+    pub parse_elapsed: Duration,
+    pub parts: Vec<PartResult>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+pub(crate) fn serialize_millis<S: Serializer>(
+    d: &Duration,
+    serializer: S
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(d.as_secs_f64() * 1000.0)
+}
+
+/// Formats a duration with a unit that keeps the number readable.
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs_f64();
+    if secs < 1e-3 {
+        format!("{:.1}µs", secs * 1e6)
+    } else if secs < 1.0 {
+        format!("{:.2}ms", secs * 1e3)
+    } else {
+        format!("{:.3}s", secs)
+    }
+}
+
+/// Parses the input once and computes the given parts, timing each phase.
+pub fn solve(
+    solution: &dyn DynSolution,
+    puzzle_input: &str,
+    parts: &[Part],
+) -> Result<DayResult, RunError> {
+    solve_with(solution, puzzle_input, &Params::new(), parts)
+}
+
+/// Like `solve`, with puzzle parameters for the parser.
+pub fn solve_with(
+    solution: &dyn DynSolution,
+    puzzle_input: &str,
+    params: &Params,
+    parts: &[Part],
+) -> Result<DayResult, RunError> {
+    let (year, day) = (solution.year(), solution.day());
+    let start = Instant::now();
+    let parsed = solution.parse_any_with(puzzle_input, params)
+        .map_err(|source| RunError::Solve { year, day, part: None, source })?;
+    let parse_elapsed = start.elapsed();
+    let parts = parts.iter().map(|&part| {
+        let start = Instant::now();
+        let answer = solution.part_any(parsed.as_ref(), part)
+            .map_err(|source| RunError::Solve { year, day, part: Some(part), source })?;
+        Ok(PartResult {
+            year,
+            day,
+            part,
+            answer,
+            elapsed: start.elapsed(),
+        })
+    }).collect::<Result<Vec<_>, RunError>>()?;
+    Ok(DayResult {
+        year,
+        day,
+        parse_elapsed,
+        parts,
+    })
+}
+
+/// Runs the given parts of a day's solution on the given input.
+pub fn run(
+    registry: &Registry,
+    year: u16,
+    day: u8,
+    parts: &[Part],
+    input: &InputSource,
+) -> Result<DayResult, RunError> {
+    let solution = registry.get(year, day)
+        .ok_or(RunError::UnknownDay { year, day })?;
+    let puzzle_input = input.read()
+        .map_err(|source| RunError::Input { input: input.clone(), source })?;
+    solve(solution, &puzzle_input, parts)
+}
+
+/// Runs the given parts of a day's solution on its puzzle input.
+pub fn run_day(
+    registry: &Registry,
+    year: u16,
+    day: u8,
+    parts: &[Part],
+    inputs: &dyn InputProvider,
+) -> Result<DayResult, RunError> {
+    let solution = registry.get(year, day)
+        .ok_or(RunError::UnknownDay { year, day })?;
+    solve(solution, &inputs.input(year, day)?, parts)
+}
+
+/// Runs both parts of every registered day, optionally only those of the
+/// given year, on their puzzle inputs.
+pub fn run_all(
+    registry: &Registry,
+    inputs: &dyn InputProvider,
+    year: Option<u16>,
+) -> Vec<Result<DayResult, RunError>> {
+    registry.days()
+        .filter(|(y, _)| year.is_none() || year == Some(*y))
+        .map(|(y, d)| run_day(registry, y, d, &[Part::One, Part::Two], inputs))
+        .collect()
+}
+
+/// Writes the answers either as one line per part or as a JSON array with
+/// one object per part.
+pub fn write_results<W: Write>(
+    out: &mut W,
+    results: &[DayResult],
+    format: OutputFormat,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Human => {
+            for result in results {
+                if results.len() > 1 {
+                    writeln!(out, "{} day {}:", result.year, result.day)?;
+                }
+                for part in &result.parts {
+                    writeln!(
+                        out, "Part {}: {} ({})",
+                        part.part, part.answer, format_duration(part.elapsed)
+                    )?;
+                }
+            }
+        },
+        OutputFormat::Json => {
+            let parts: Vec<&PartResult> = results.iter()
+                .flat_map(|result| &result.parts)
+                .collect();
+            serde_json::to_writer_pretty(&mut *out, &parts)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a table with the answers and the time spent in each phase of
+/// each day, followed by the total time.
+pub fn write_table<W: Write>(out: &mut W, results: &[DayResult]) -> io::Result<()> {
+    let header = [
+        "Year", "Day", "Part 1", "Part 2", "Parse", "Time 1", "Time 2", "Total"
+    ];
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut total = Duration::ZERO;
+    for result in results {
+        let mut answers = [String::from("-"), String::from("-")];
+        let mut times = [String::from("-"), String::from("-")];
+        let mut day_total = result.parse_elapsed;
+        for part in &result.parts {
+            let i = match part.part { Part::One => 0, Part::Two => 1 };
+            answers[i] = part.answer.to_string();
+            times[i] = format_duration(part.elapsed);
+            day_total += part.elapsed;
+        }
+        total += day_total;
+        let [answer1, answer2] = answers;
+        let [time1, time2] = times;
+        rows.push(vec![
+            result.year.to_string(),
+            result.day.to_string(),
+            answer1,
+            answer2,
+            format_duration(result.parse_elapsed),
+            time1,
+            time2,
+            format_duration(day_total),
+        ]);
+    }
+
+    write_columns(out, &header, &rows)?;
+    writeln!(out, "Total: {}", format_duration(total))
+}
+
+/// Writes a header and rows as right-aligned columns.
+pub(crate) fn write_columns<W: Write>(
+    out: &mut W,
+    header: &[&str],
+    rows: &[Vec<String>],
+) -> io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let write_row = |out: &mut W, row: &[&str]| -> io::Result<()> {
+        let cells: Vec<String> = row.iter().zip(&widths)
+            .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+            .collect();
+        writeln!(out, "{}", cells.join(" | ").trim_end())
+    };
+    write_row(out, header)?;
+    let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    writeln!(out, "{}", rule.join("-+-"))?;
+    for row in rows {
+        write_row(out, &row.iter().map(String::as_str).collect::<Vec<_>>())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_output() {
+        let results = vec![DayResult {
+            year: 2020,
+            day: 1,
+            parse_elapsed: Duration::from_millis(1),
+            parts: vec![
+                PartResult {
+                    year: 2020,
+                    day: 1,
+                    part: Part::One,
+                    answer: Answer::Unsigned(42),
+                    elapsed: Duration::from_micros(1500),
+                },
+                PartResult {
+                    year: 2020,
+                    day: 1,
+                    part: Part::Two,
+                    answer: Answer::Unsolved,
+                    elapsed: Duration::from_millis(2),
+                },
+            ],
+        }];
+        let mut out: Vec<u8> = Vec::new();
+        write_results(&mut out, &results, OutputFormat::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json, serde_json::json!([
+            {"year": 2020, "day": 1, "part": 1, "answer": 42, "elapsed_ms": 1.5},
+            {"year": 2020, "day": 1, "part": 2, "answer": null, "elapsed_ms": 2.0},
+        ]));
+    }
+
+    #[test]
+    fn test_table_output() {
+        let results = vec![DayResult {
+            year: 2021,
+            day: 3,
+            parse_elapsed: Duration::from_millis(1),
+            parts: vec![PartResult {
+                year: 2021,
+                day: 3,
+                part: Part::Two,
+                answer: Answer::Signed(-7),
+                elapsed: Duration::from_millis(2),
+            }],
+        }];
+        let mut out: Vec<u8> = Vec::new();
+        write_table(&mut out, &results).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "\
+Year | Day | Part 1 | Part 2 |  Parse | Time 1 | Time 2 |  Total
+-----+-----+--------+--------+--------+--------+--------+-------
+2021 |   3 |      - |     -7 | 1.00ms |      - | 2.00ms | 3.00ms
+Total: 3.00ms
+");
+    }
+
+    struct Failing;
+
+    impl crate::Solution for Failing {
+        const YEAR: u16 = 1970;
+        const DAY: u8 = 2;
+
+        type Input = u32;
+
+        fn parse(&self, input: &str) -> Result<u32, AoCError> {
+            crate::parse::value(input.trim(), 1, 1)
+        }
+
+        fn part1(&self, _input: &u32) -> Result<Answer, AoCError> {
+            Err(AoCError::no_solution("Nothing to find"))
+        }
+    }
+
+    #[test]
+    fn test_solve_errors() {
+        let parse_error = solve(&Failing, "x", &[Part::One]).unwrap_err();
+        assert!(matches!(parse_error, RunError::Solve { part: None, .. }));
+        assert_eq!(parse_error.exit_code(), 4);
+        assert_eq!(
+            parse_error.to_string(),
+            "1970 day 2: Parse error at line 1, column 1: \
+             Invalid value \"x\": invalid digit found in string"
+        );
+
+        let part_error = solve(&Failing, "1", &[Part::One]).unwrap_err();
+        assert_eq!(part_error.exit_code(), 5);
+        assert_eq!(
+            part_error.to_string(),
+            "1970 day 2 part 1: No solution: Nothing to find"
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_nanos(1500)), "1.5µs");
+        assert_eq!(format_duration(Duration::from_micros(2500)), "2.50ms");
+        assert_eq!(format_duration(Duration::from_millis(1500)), "1.500s");
+    }
+}
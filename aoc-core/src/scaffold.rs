@@ -0,0 +1,210 @@
+//! Generates the files for a new day: the solution module in the year's
+//! crate, its entry in the `days!` list, and the puzzle folder with an
+//! empty input, an example with a placeholder answer (so that the generated
+//! example test fails until the example is filled in) and an answers file.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::examples;
+use crate::input;
+use crate::answers;
+
+#[derive(Debug)]
+pub enum ScaffoldError {
+    /// There is no crate for the year yet.
+    NoYearCrate(PathBuf),
+    /// The day already has a module or is already registered.
+    Exists(PathBuf),
+    /// The `days!` list of the year's crate could not be found.
+    NoDaysList(PathBuf),
+    Io { path: PathBuf, source: io::Error },
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScaffoldError::NoYearCrate(path) => write!(
+                f, "There is no crate for this year at \"{}\"", path.display()
+            ),
+            ScaffoldError::Exists(path) => write!(
+                f, "The day already exists in \"{}\"", path.display()
+            ),
+            ScaffoldError::NoDaysList(path) => write!(
+                f, "Could not find the aoc_core::days! list in \"{}\"", path.display()
+            ),
+            ScaffoldError::Io { path, source } => write!(
+                f, "Could not write \"{}\": {}", path.display(), source
+            ),
+        }
+    }
+}
+
+impl Error for ScaffoldError {}
+
+/// The source directory of a year's crate, e.g. `<root>/2021/aoc_rust/src`.
+pub fn crate_src_dir(root: &Path, year: u16) -> PathBuf {
+    root.join(year.to_string()).join("aoc_rust").join("src")
+}
+
+/// The source of a new day's module, which parses the input into lines
+/// and leaves both parts unsolved.
+pub fn module_source(year: u16, day: u8) -> String {
+    format!("\
+use aoc_core::{{AoCError, Answer, Solution, parse}};
+
+pub struct Day{day:02};
+
+impl Solution for Day{day:02} {{
+    const YEAR: u16 = {year};
+    const DAY: u8 = {day};
+
+    type Input = Vec<String>;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>, AoCError> {{
+        Ok(parse::lines(input).map(|(_, line)| line.to_string()).collect())
+    }}
+
+    fn part1(&self, _lines: &Vec<String>) -> Result<Answer, AoCError> {{
+        Ok(Answer::Unsolved)
+    }}
+}}
+", year = year, day = day)
+}
+
+/// Adds `dayNN::DayNN,` to the `days!` list in the source of a crate's
+/// `lib.rs`, keeping the list ordered. Returns `None` if there is no such
+/// list or if the day is in it already.
+pub fn register_day(lib_source: &str, day: u8) -> Option<String> {
+    let lines: Vec<&str> = lib_source.lines().collect();
+    let start = lines.iter().position(|line| line.trim() == "aoc_core::days! {")?;
+    let end = start + lines[start..].iter().position(|line| line.trim() == "}")?;
+    let entry = format!("day{:02}::Day{:02},", day, day);
+    let mut insert_at = end;
+    for (i, line) in lines.iter().enumerate().take(end).skip(start + 1) {
+        let existing = line.trim();
+        if existing == entry { return None; }
+        if existing > entry.as_str() {
+            insert_at = i;
+            break;
+        }
+    }
+    let mut new_lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    new_lines.insert(insert_at, format!("    {}", entry));
+    Some(new_lines.join("\n") + "\n")
+}
+
+fn write_new(path: &Path, contents: &str) -> Result<(), ScaffoldError> {
+    if path.exists() { return Err(ScaffoldError::Exists(path.to_path_buf())); }
+    fs::write(path, contents)
+        .map_err(|source| ScaffoldError::Io { path: path.to_path_buf(), source })
+}
+
+/// Creates the module and puzzle files of a new day and registers the
+/// module. Returns the paths of all created or changed files.
+pub fn scaffold(root: &Path, year: u16, day: u8) -> Result<Vec<PathBuf>, ScaffoldError> {
+    let src_dir = crate_src_dir(root, year);
+    let lib_path = src_dir.join("lib.rs");
+    if !lib_path.is_file() { return Err(ScaffoldError::NoYearCrate(src_dir)); }
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| ScaffoldError::Io { path, source }
+    };
+
+    // Check everything before writing anything, so that a failure doesn't
+    // leave half a day behind.
+    let module_path = src_dir.join(format!("day{:02}.rs", day));
+    if module_path.exists() { return Err(ScaffoldError::Exists(module_path)); }
+    let lib_source = fs::read_to_string(&lib_path).map_err(io_error(&lib_path))?;
+    let new_lib_source = register_day(&lib_source, day).ok_or_else(|| {
+        match lib_source.contains(&format!("day{:02}::Day{:02}", day, day)) {
+            true => ScaffoldError::Exists(lib_path.clone()),
+            false => ScaffoldError::NoDaysList(lib_path.clone()),
+        }
+    })?;
+
+    let mut changed: Vec<PathBuf> = Vec::new();
+    write_new(&module_path, &module_source(year, day))?;
+    changed.push(module_path);
+    fs::write(&lib_path, new_lib_source).map_err(io_error(&lib_path))?;
+    changed.push(lib_path);
+
+    let day_dir = input::day_dir(root, year, day);
+    fs::create_dir_all(&day_dir).map_err(io_error(&day_dir))?;
+    let puzzle_files = [
+        (input::input_path(root, year, day), ""),
+        (input::example_path(root, year, day, "1"), ""),
+        (
+            examples::example_answers_path(root, year, day, "1"),
+            "# The answers given in the puzzle description for example1.txt.\n\
+             part1 = \"TODO\"\n",
+        ),
+        (
+            answers::answers_path(root, year, day),
+            "# The accepted answers, e.g.\n# part1 = 42\n",
+        ),
+    ];
+    for (path, contents) in puzzle_files {
+        // Keep files that were already downloaded or written by hand.
+        if path.exists() { continue; }
+        fs::write(&path, contents).map_err(io_error(&path))?;
+        changed.push(path);
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TempDir;
+
+    const LIB: &str = "\
+//! Solutions.
+
+aoc_core::days! {
+    day01::Day01,
+    day05::Day05,
+}
+";
+
+    #[test]
+    fn test_register_day() {
+        assert_eq!(register_day(LIB, 3).unwrap(), "\
+//! Solutions.
+
+aoc_core::days! {
+    day01::Day01,
+    day03::Day03,
+    day05::Day05,
+}
+");
+        assert!(register_day(LIB, 6).unwrap().contains("day05::Day05,\n    day06::Day06,\n}"));
+        assert!(register_day(LIB, 12).unwrap().contains("day05::Day05,\n    day12::Day12,\n}"));
+        assert_eq!(register_day(LIB, 5), None);
+        assert_eq!(register_day("fn main() {}", 5), None);
+    }
+
+    #[test]
+    fn test_scaffold() {
+        let temp = TempDir::new("scaffold");
+        let root = temp.path();
+        let src_dir = crate_src_dir(root, 1970);
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::write(src_dir.join("lib.rs"), LIB).unwrap();
+
+        let changed = scaffold(root, 1970, 2).unwrap();
+        let module = fs::read_to_string(src_dir.join("day02.rs")).unwrap();
+        let lib = fs::read_to_string(src_dir.join("lib.rs")).unwrap();
+        let again = scaffold(root, 1970, 2);
+        let other_year = scaffold(root, 1971, 2);
+
+        assert_eq!(changed.len(), 6);
+        assert!(module.contains("pub struct Day02;"));
+        assert!(module.contains("const YEAR: u16 = 1970;\n    const DAY: u8 = 2;"));
+        assert!(lib.contains("day01::Day01,\n    day02::Day02,\n    day05::Day05,"));
+        assert!(matches!(again, Err(ScaffoldError::Exists(_))));
+        assert!(matches!(other_year, Err(ScaffoldError::NoYearCrate(_))));
+    }
+}
@@ -0,0 +1,276 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt;
+use serde::{Serialize, Serializer};
+use crate::error::AoCError;
+
+/// The answer to one part of a puzzle.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Answer {
+    Signed(i64),
+    Unsigned(u64),
+    Text(String),
+    /// The part has not been solved (yet).
+    Unsolved,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Signed(n) => write!(f, "{}", n),
+            Answer::Unsigned(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Unsolved => write!(f, "-"),
+        }
+    }
+}
+
+/// Numbers are serialized as JSON numbers, text as strings and unsolved
+/// parts as `null`.
+impl Serialize for Answer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Answer::Signed(n) => serializer.serialize_i64(*n),
+            Answer::Unsigned(n) => serializer.serialize_u64(*n),
+            Answer::Text(s) => serializer.serialize_str(s),
+            Answer::Unsolved => serializer.serialize_none(),
+        }
+    }
+}
+
+macro_rules! impl_answer_from {
+    ($variant:ident, $target:ty, $($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Answer { Answer::$variant(n as $target) }
+        })*
+    };
+}
+
+impl_answer_from!(Signed, i64, i32, i64);
+impl_answer_from!(Unsigned, u64, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Answer { Answer::Text(s) }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Answer { Answer::Text(s.to_string()) }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn from_number(n: u8) -> Option<Part> {
+        match n {
+            1 => Some(Part::One),
+            2 => Some(Part::Two),
+            _ => None,
+        }
+    }
+
+    pub fn number(self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl Serialize for Part {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.number())
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// Puzzle parameters that are not part of the input, e.g. a size that the
+/// puzzle description fixes for the real input but not for its examples.
+/// Examples set them in the `[params]` table of their answers file.
+pub type Params = BTreeMap<String, i64>;
+
+/// A solution for a single day of the calendar.
+///
+/// The puzzle input is parsed once, and both parts are computed from the
+/// parsed representation.
+pub trait Solution {
+    const YEAR: u16;
+    const DAY: u8;
+
+    type Input: 'static;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, AoCError>;
+
+    /// Parses the input with the given parameters. Solutions without any
+    /// parameters reject them.
+    fn parse_with(&self, input: &str, params: &Params) -> Result<Self::Input, AoCError> {
+        match params.keys().next() {
+            Some(name) => Err(AoCError::unknown_param(name)),
+            None => self.parse(input),
+        }
+    }
+
+    fn part1(&self, input: &Self::Input) -> Result<Answer, AoCError>;
+
+    fn part2(&self, _input: &Self::Input) -> Result<Answer, AoCError> {
+        Ok(Answer::Unsolved)
+    }
+}
+
+/// Object-safe counterpart to `Solution` so that solutions with different
+/// input types can be stored in the same `Registry`.
+pub trait DynSolution {
+    fn year(&self) -> u16;
+    fn day(&self) -> u8;
+    fn parse_any(&self, input: &str) -> Result<Box<dyn Any>, AoCError>;
+    fn parse_any_with(&self, input: &str, params: &Params) -> Result<Box<dyn Any>, AoCError>;
+    fn part_any(&self, input: &dyn Any, part: Part) -> Result<Answer, AoCError>;
+}
+
+impl<S: Solution> DynSolution for S {
+    fn year(&self) -> u16 { S::YEAR }
+
+    fn day(&self) -> u8 { S::DAY }
+
+    fn parse_any(&self, input: &str) -> Result<Box<dyn Any>, AoCError> {
+        Ok(Box::new(self.parse(input)?))
+    }
+
+    fn parse_any_with(&self, input: &str, params: &Params) -> Result<Box<dyn Any>, AoCError> {
+        Ok(Box::new(self.parse_with(input, params)?))
+    }
+
+    fn part_any(&self, input: &dyn Any, part: Part) -> Result<Answer, AoCError> {
+        let input = input.downcast_ref::<S::Input>()
+            .expect("Parsed input does not belong to this solution");
+        match part {
+            Part::One => self.part1(input),
+            Part::Two => self.part2(input),
+        }
+    }
+}
+
+/// All known solutions, indexed by (year, day).
+#[derive(Default)]
+pub struct Registry {
+    solutions: BTreeMap<(u16, u8), &'static dyn DynSolution>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    pub fn register(&mut self, solution: &'static dyn DynSolution) {
+        let key = (solution.year(), solution.day());
+        if self.solutions.insert(key, solution).is_some() {
+            panic!("Solution for {}, day {} registered twice", key.0, key.1);
+        }
+    }
+
+    pub fn get(&self, year: u16, day: u8) -> Option<&'static dyn DynSolution> {
+        self.solutions.get(&(year, day)).copied()
+    }
+
+    /// Returns (year, day) of all registered solutions in calendar order.
+    pub fn days(&self) -> impl Iterator<Item = (u16, u8)> + '_ {
+        self.solutions.keys().copied()
+    }
+
+    /// Parses the input and computes a single part, or returns `None` if
+    /// no solution is registered for the given day.
+    pub fn run_part(
+        &self,
+        year: u16,
+        day: u8,
+        part: Part,
+        input: &str
+    ) -> Option<Result<Answer, AoCError>> {
+        let solution = self.get(year, day)?;
+        Some(solution.parse_any(input)
+            .and_then(|parsed| solution.part_any(parsed.as_ref(), part)))
+    }
+}
+
+/// Declares the day modules of a year's crate and generates a `register`
+/// function that adds each module's solution to a `Registry`, so that adding
+/// a day only takes one line. Also generates a test per day that checks
+/// the day's example inputs, see `examples`.
+#[macro_export]
+macro_rules! days {
+    ($($module:ident::$solution:ident),* $(,)?) => {
+        $(pub mod $module;)*
+
+        pub fn register(registry: &mut $crate::Registry) {
+            $(registry.register(&$module::$solution);)*
+        }
+
+        #[cfg(test)]
+        mod example_tests {
+            $(
+                #[test]
+                fn $module() {
+                    $crate::examples::assert_examples(
+                        &super::$module::$solution,
+                        env!("CARGO_MANIFEST_DIR")
+                    );
+                }
+            )*
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Dummy;
+
+    impl Solution for Dummy {
+        const YEAR: u16 = 1970;
+        const DAY: u8 = 1;
+
+        type Input = Vec<i64>;
+
+        fn parse(&self, input: &str) -> Result<Vec<i64>, AoCError> {
+            crate::parse::comma_separated(input, 1)
+        }
+
+        fn part1(&self, input: &Vec<i64>) -> Result<Answer, AoCError> {
+            Ok(input.iter().sum::<i64>().into())
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = Registry::new();
+        registry.register(&Dummy);
+        assert_eq!(registry.days().collect::<Vec<_>>(), vec![(1970, 1)]);
+        assert_eq!(
+            registry.run_part(1970, 1, Part::One, "1,-2,3"),
+            Some(Ok(Answer::Signed(2)))
+        );
+        assert_eq!(
+            registry.run_part(1970, 1, Part::Two, "1,-2,3"),
+            Some(Ok(Answer::Unsolved))
+        );
+        assert_eq!(
+            registry.run_part(1970, 1, Part::One, "1,x"),
+            Some(Err(AoCError::parse(
+                1, 3, "Invalid value \"x\": invalid digit found in string"
+            )))
+        );
+        assert_eq!(registry.run_part(1970, 2, Part::One, "1"), None);
+    }
+}
@@ -0,0 +1,130 @@
+//! Compares computed answers with the expected ones to detect regressions.
+
+use std::fmt;
+use std::io::{self, Write};
+use crate::answers::ExpectedAnswers;
+use crate::runner::DayResult;
+use crate::solution::{Answer, Part};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String },
+    /// There is no expected answer to compare with.
+    Missing,
+}
+
+#[derive(Clone, Debug)]
+pub struct PartVerdict {
+    pub year: u16,
+    pub day: u8,
+    pub part: Part,
+    pub answer: Answer,
+    pub verdict: Verdict,
+}
+
+impl fmt::Display for PartVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} day {} part {}: ", self.year, self.day, self.part)?;
+        match &self.verdict {
+            Verdict::Pass => write!(f, "pass ({})", self.answer),
+            Verdict::Fail { expected } => write!(
+                f, "FAIL (got {}, expected {})", self.answer, expected
+            ),
+            Verdict::Missing => write!(f, "missing (got {})", self.answer),
+        }
+    }
+}
+
+/// Checks each computed part of a day against the expected answers.
+pub fn verify(result: &DayResult, expected: &ExpectedAnswers) -> Vec<PartVerdict> {
+    result.parts.iter().map(|part| {
+        let verdict = match expected.get(part.part) {
+            None => Verdict::Missing,
+            Some(expected) if part.answer.to_string() == expected => Verdict::Pass,
+            Some(expected) => Verdict::Fail { expected: expected.to_string() },
+        };
+        PartVerdict {
+            year: part.year,
+            day: part.day,
+            part: part.part,
+            answer: part.answer.clone(),
+            verdict,
+        }
+    }).collect()
+}
+
+/// Writes one line per part and a summary, and returns the number of
+/// failed parts.
+pub fn write_report<W: Write>(
+    out: &mut W,
+    verdicts: &[PartVerdict],
+) -> io::Result<usize> {
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut missing = 0;
+    for verdict in verdicts {
+        writeln!(out, "{}", verdict)?;
+        match verdict.verdict {
+            Verdict::Pass => passed += 1,
+            Verdict::Fail { .. } => failed += 1,
+            Verdict::Missing => missing += 1,
+        }
+    }
+    writeln!(
+        out, "{} passed, {} failed, {} missing", passed, failed, missing
+    )?;
+    Ok(failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::runner::PartResult;
+
+    #[test]
+    fn test_verify() {
+        let part = |part: Part, answer: Answer| PartResult {
+            year: 2020,
+            day: 10,
+            part,
+            answer,
+            elapsed: Duration::ZERO,
+        };
+        let result = DayResult {
+            year: 2020,
+            day: 10,
+            parse_elapsed: Duration::ZERO,
+            parts: vec![
+                part(Part::One, Answer::Unsigned(2170)),
+                part(Part::Two, Answer::Unsigned(1)),
+            ],
+        };
+        let expected = ExpectedAnswers {
+            part1: Some(String::from("2170")),
+            part2: None,
+        };
+        let verdicts = verify(&result, &expected);
+        assert_eq!(verdicts[0].verdict, Verdict::Pass);
+        assert_eq!(verdicts[1].verdict, Verdict::Missing);
+
+        let expected = ExpectedAnswers {
+            part1: Some(String::from("2170")),
+            part2: Some(String::from("24803586664192")),
+        };
+        let verdicts = verify(&result, &expected);
+        assert_eq!(
+            verdicts[1].verdict,
+            Verdict::Fail { expected: String::from("24803586664192") }
+        );
+
+        let mut out: Vec<u8> = Vec::new();
+        assert_eq!(write_report(&mut out, &verdicts).unwrap(), 1);
+        assert_eq!(String::from_utf8(out).unwrap(), "\
+2020 day 10 part 1: pass (2170)
+2020 day 10 part 2: FAIL (got 1, expected 24803586664192)
+1 passed, 1 failed, 0 missing
+");
+    }
+}
@@ -0,0 +1,983 @@
+extern crate clap;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+use std::path::{Path, PathBuf};
+use aoc_core::{Answer, Part, Registry, answers, bench, debug, examples, fetch, input, runner, scaffold, verify};
+use aoc_core::fetch::HttpFetcher;
+use aoc_core::input::InputSource;
+use aoc_core::provider::{CachedInputs, InputProvider, LocalInputs};
+use aoc_core::runner::{OutputFormat, RunError};
+
+fn main() {
+    let matches = App::new("Advent of Code solutions")
+        .author("Lukas Stratmann")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(Arg::with_name("root")
+             .long("root")
+             .takes_value(true)
+             .value_name("DIR")
+             .global(true)
+             .help("Directory that holds the <year>/<NN>/ puzzle folders \
+                    [default: the repository root]"))
+        .arg(Arg::with_name("verbose")
+             .short("v")
+             .long("verbose")
+             .global(true)
+             .help("Print debug output of the solutions to stderr"))
+        .subcommand(SubCommand::with_name("list")
+             .about("Lists the days that have a solution"))
+        .subcommand(SubCommand::with_name("run")
+             .about("Runs the solution of one day or of all days")
+             .after_help("EXIT CODES:\n    \
+                          0    all parts were solved\n    \
+                          1    invalid arguments\n    \
+                          2    no solution is registered for the day\n    \
+                          3    the input could not be read or downloaded\n    \
+                          4    the input could not be parsed\n    \
+                          5    the puzzle has no solution for the input")
+             .arg(year_arg())
+             .arg(Arg::with_name("day")
+                  .short("d")
+                  .long("day")
+                  .takes_value(true)
+                  .value_name("DAY")
+                  .required_unless("all")
+                  .help("Day in the calendar"))
+             .arg(Arg::with_name("all")
+                  .long("all")
+                  .conflicts_with_all(&["day", "part", "puzzle_input", "example"])
+                  .help("Run every registered day (of the given year) \
+                         and print a timing table"))
+             .arg(Arg::with_name("part")
+                  .short("p")
+                  .long("part")
+                  .takes_value(true)
+                  .value_name("PART")
+                  .possible_values(&["1", "2"])
+                  .help("Only compute this part of the puzzle"))
+             .arg(Arg::with_name("puzzle_input")
+                  .short("i")
+                  .long("puzzle_input")
+                  .value_name("FILE")
+                  .help("The puzzle input file, or \"-\" to read from stdin \
+                         [default: <year>/<NN>/input, which is downloaded if \
+                         it is missing and a session cookie is set]"))
+             .arg(Arg::with_name("example")
+                  .long("example")
+                  .takes_value(true)
+                  .value_name("N")
+                  .conflicts_with("puzzle_input")
+                  .help("Use the example input <year>/<NN>/example<N>.txt"))
+             .arg(Arg::with_name("json")
+                  .long("json")
+                  .help("Print the answers as JSON")))
+        .subcommand(SubCommand::with_name("bench")
+             .about("Times the phases of the solutions repeatedly and reports \
+                     the median and percentiles")
+             .arg(year_arg())
+             .arg(Arg::with_name("day")
+                  .short("d")
+                  .long("day")
+                  .takes_value(true)
+                  .value_name("DAY")
+                  .help("Only benchmark this day"))
+             .arg(Arg::with_name("samples")
+                  .short("n")
+                  .long("samples")
+                  .takes_value(true)
+                  .value_name("N")
+                  .default_value("10")
+                  .help("How often each phase is timed"))
+             .arg(Arg::with_name("budget")
+                  .long("budget")
+                  .takes_value(true)
+                  .value_name("SECONDS")
+                  .default_value("10")
+                  .help("Take fewer samples of a phase once this much time \
+                         has been spent on it"))
+             .arg(Arg::with_name("baseline")
+                  .long("baseline")
+                  .takes_value(true)
+                  .value_name("FILE")
+                  .help("Compare the medians with the output of an earlier \
+                         run with --json"))
+             .arg(Arg::with_name("json")
+                  .long("json")
+                  .help("Print the timings as JSON, e.g. to save a baseline")))
+        .subcommand(SubCommand::with_name("verify")
+             .about("Reruns the solutions and compares the answers with \
+                     <year>/<NN>/answers.toml")
+             .arg(year_arg())
+             .arg(Arg::with_name("day")
+                  .short("d")
+                  .long("day")
+                  .takes_value(true)
+                  .value_name("DAY")
+                  .help("Only verify this day")))
+        .subcommand(SubCommand::with_name("fetch")
+             .about("Downloads the puzzle input of a day to <year>/<NN>/input \
+                     unless it is there already")
+             .after_help(SESSION_HELP)
+             .arg(year_arg())
+             .arg(Arg::with_name("day")
+                  .short("d")
+                  .long("day")
+                  .takes_value(true)
+                  .value_name("DAY")
+                  .required(true)
+                  .help("Day in the calendar")))
+        .subcommand(SubCommand::with_name("submit")
+             .about("Submits an answer and records it in \
+                     <year>/<NN>/answers.toml if it is correct")
+             .after_help(SESSION_HELP)
+             .arg(year_arg())
+             .arg(Arg::with_name("day")
+                  .short("d")
+                  .long("day")
+                  .takes_value(true)
+                  .value_name("DAY")
+                  .required(true)
+                  .help("Day in the calendar"))
+             .arg(Arg::with_name("part")
+                  .short("p")
+                  .long("part")
+                  .takes_value(true)
+                  .value_name("PART")
+                  .possible_values(&["1", "2"])
+                  .required(true)
+                  .help("Part of the puzzle"))
+             .arg(Arg::with_name("answer")
+                  .value_name("ANSWER")
+                  .help("The answer to submit [default: the answer computed \
+                         by the solution]")))
+        .subcommand(SubCommand::with_name("seats")
+             .about("Simulates the seating of 2020 day 11 with any rule and \
+                     reports whether it converges, cycles or keeps changing")
+             .arg(Arg::with_name("rule")
+                  .long("rule")
+                  .takes_value(true)
+                  .value_name("CONFIG")
+                  .default_value("neighbors=adjacent,tolerance=4")
+                  .help("Comma-separated settings: topology=bounded|torus|hex, \
+                         neighbors=von-neumann|adjacent|radius:R|visible|visible:D, \
+                         tolerance=N or rule=<B/S rule>, e.g. \
+                         \"neighbors=visible,tolerance=5\" for part 2"))
+             .arg(Arg::with_name("max_steps")
+                  .long("max-steps")
+                  .takes_value(true)
+                  .value_name("N")
+                  .default_value("10000")
+                  .help("Give up after this many steps"))
+             .arg(Arg::with_name("puzzle_input")
+                  .short("i")
+                  .long("puzzle_input")
+                  .value_name("FILE")
+                  .help("The puzzle input file, or \"-\" to read from stdin \
+                         [default: 2020/11/input]"))
+             .arg(Arg::with_name("example")
+                  .long("example")
+                  .takes_value(true)
+                  .value_name("N")
+                  .conflicts_with("puzzle_input")
+                  .help("Use the example input 2020/11/example<N>.txt"))
+             .arg(Arg::with_name("generate")
+                  .long("generate")
+                  .takes_value(true)
+                  .value_name("WxH")
+                  .conflicts_with_all(&["puzzle_input", "example"])
+                  .help("Use a random layout of this size, e.g. 500x500"))
+             .arg(Arg::with_name("seed")
+                  .long("seed")
+                  .takes_value(true)
+                  .value_name("N")
+                  .default_value("1")
+                  .help("Seed of the layout of --generate"))
+             .arg(Arg::with_name("bench")
+                  .long("bench")
+                  .conflicts_with_all(&["frames", "animate", "images"])
+                  .help("Time the incremental engine against the full \
+                         simulation"))
+             .arg(Arg::with_name("frames")
+                  .long("frames")
+                  .takes_value(true)
+                  .value_name("FILE")
+                  .help("Write every generation as text to this file, or \"-\" \
+                         for stdout"))
+             .arg(Arg::with_name("animate")
+                  .long("animate")
+                  .help("Replay the generations in the terminal"))
+             .arg(Arg::with_name("delay")
+                  .long("delay")
+                  .takes_value(true)
+                  .value_name("MS")
+                  .default_value("100")
+                  .help("Milliseconds between the frames of --animate"))
+             .arg(Arg::with_name("images")
+                  .long("images")
+                  .takes_value(true)
+                  .value_name("DIR")
+                  .help("Write every generation as an image into this directory"))
+             .arg(Arg::with_name("format")
+                  .long("format")
+                  .takes_value(true)
+                  .possible_values(&["png", "ppm"])
+                  .default_value("png")
+                  .help("Image format of --images"))
+             .arg(Arg::with_name("scale")
+                  .long("scale")
+                  .takes_value(true)
+                  .value_name("N")
+                  .default_value("4")
+                  .help("Pixels per seat in --images")))
+        .subcommand(SubCommand::with_name("bags")
+             .about("Inspects the bag rules of 2020 day 7")
+             .setting(AppSettings::SubcommandRequiredElseHelp)
+             .arg(Arg::with_name("puzzle_input")
+                  .short("i")
+                  .long("puzzle_input")
+                  .value_name("FILE")
+                  .help("The puzzle input file, or \"-\" to read from stdin \
+                         [default: 2020/07/input]"))
+             .arg(Arg::with_name("example")
+                  .long("example")
+                  .takes_value(true)
+                  .value_name("N")
+                  .conflicts_with("puzzle_input")
+                  .help("Use the example input 2020/07/example<N>.txt"))
+             .subcommand(SubCommand::with_name("export")
+                  .about("Writes the containment graph to stdout, e.g. for \
+                          `dot -Tsvg`")
+                  .arg(Arg::with_name("format")
+                       .long("format")
+                       .takes_value(true)
+                       .possible_values(&["dot", "json"])
+                       .default_value("dot")
+                       .help("Graphviz DOT or JSON"))
+                  .arg(Arg::with_name("highlight")
+                       .long("highlight")
+                       .takes_value(true)
+                       .value_name("COLOR")
+                       .help("Highlight the bags around this colour, e.g. \
+                              \"shiny gold\""))
+                  .arg(Arg::with_name("direction")
+                       .long("direction")
+                       .takes_value(true)
+                       .possible_values(&["contents", "containers", "both"])
+                       .requires("highlight")
+                       .help("Highlight the bags inside, the bags outside \
+                              or both [default: both]"))
+                  .arg(Arg::with_name("only")
+                       .long("only")
+                       .requires("highlight")
+                       .help("Leave out everything that isn't highlighted")))
+             .subcommand(SubCommand::with_name("paths")
+                  .about("Lists the chains of bags that lead to bags of a colour, \
+                          with how many of them each outermost bag holds")
+                  .arg(Arg::with_name("color")
+                       .required(true)
+                       .value_name("COLOR")
+                       .help("The innermost colour, e.g. \"shiny gold\""))
+                  .arg(Arg::with_name("from")
+                       .long("from")
+                       .takes_value(true)
+                       .value_name("COLOR")
+                       .help("Start at this colour [default: all outermost \
+                              bags]"))
+                  .arg(Arg::with_name("limit")
+                       .long("limit")
+                       .takes_value(true)
+                       .value_name("N")
+                       .default_value("20")
+                       .help("List at most this many paths")))
+             .subcommand(SubCommand::with_name("breakdown")
+                  .about("Shows how many of the bags inside a bag each kind \
+                          of its contents accounts for")
+                  .arg(Arg::with_name("color")
+                       .required(true)
+                       .value_name("COLOR")
+                       .help("The outer colour, e.g. \"shiny gold\"")))
+             .subcommand(SubCommand::with_name("deepest")
+                  .about("Finds the longest chain of bags inside each other")
+                  .arg(Arg::with_name("color")
+                       .value_name("COLOR")
+                       .help("Start at this colour [default: any colour]"))))
+        .subcommand(SubCommand::with_name("console")
+             .about("Steps through the boot code of 2020 day 8 in a debugger \
+                     that reads its commands from stdin (\"help\" lists them)")
+             .arg(Arg::with_name("puzzle_input")
+                  .short("i")
+                  .long("puzzle_input")
+                  .value_name("FILE")
+                  .help("The puzzle input file [default: 2020/08/input]"))
+             .arg(Arg::with_name("example")
+                  .long("example")
+                  .takes_value(true)
+                  .value_name("N")
+                  .conflicts_with("puzzle_input")
+                  .help("Use the example input 2020/08/example<N>.txt"))
+             .arg(Arg::with_name("fixed")
+                  .long("fixed")
+                  .help("Debug the program with the corrupted instruction \
+                         of part 2 flipped"))
+             .arg(Arg::with_name("break")
+                  .short("b")
+                  .long("break")
+                  .takes_value(true)
+                  .multiple(true)
+                  .number_of_values(1)
+                  .value_name("PC|OP")
+                  .help("Pause before the instruction at a pc, or before \
+                         every nop/acc/jmp"))
+             .arg(Arg::with_name("max_steps")
+                  .long("max-steps")
+                  .takes_value(true)
+                  .value_name("N")
+                  .help("Halt after this many steps"))
+             .arg(Arg::with_name("allow_repeats")
+                  .long("allow-repeats")
+                  .requires("max_steps")
+                  .help("Don't halt when an instruction is about to be \
+                         executed a second time, which needs --max-steps \
+                         so that a loop can't run forever"))
+             .arg(Arg::with_name("run")
+                  .long("run")
+                  .help("Run to the end without asking for commands and \
+                         print the trace")))
+        .subcommand(SubCommand::with_name("new")
+             .about("Creates the module and puzzle files of a new day and \
+                     registers the module in the year's crate")
+             .arg(Arg::with_name("year")
+                  .short("y")
+                  .long("year")
+                  .takes_value(true)
+                  .value_name("YEAR")
+                  .required(true)
+                  .help("Year of the calendar"))
+             .arg(Arg::with_name("day")
+                  .short("d")
+                  .long("day")
+                  .takes_value(true)
+                  .value_name("DAY")
+                  .required(true)
+                  .help("Day of the calendar")))
+        .get_matches();
+
+    debug::set_verbose(matches.is_present("verbose"));
+    let root: PathBuf = match matches.value_of("root") {
+        Some(root) => PathBuf::from(root),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf(),
+    };
+
+    let session = fetch::session_token(&root);
+    let inputs: Box<dyn InputProvider> = match &session {
+        Some(session) => Box::new(CachedInputs::new(&root, HttpFetcher::new(session))),
+        None => Box::new(LocalInputs::new(&root)),
+    };
+
+    let mut registry = Registry::new();
+    aoc_2020::register(&mut registry);
+    aoc_2021::register(&mut registry);
+
+    match matches.subcommand() {
+        ("list", _) => {
+            for (year, day) in registry.days() {
+                println!("{} day {}", year, day);
+            }
+        },
+        ("run", Some(m)) => run(&registry, &root, inputs.as_ref(), m),
+        ("bench", Some(m)) => bench(&registry, inputs.as_ref(), m),
+        ("verify", Some(m)) => verify(&registry, &root, inputs.as_ref(), m),
+        ("seats", Some(m)) => seats(&root, inputs.as_ref(), m),
+        ("bags", Some(m)) => bags(&root, inputs.as_ref(), m),
+        ("console", Some(m)) => console(&root, inputs.as_ref(), m),
+        ("fetch", Some(m)) => fetch_input(&registry, &root, session.as_deref(), m),
+        ("submit", Some(m)) => submit(&registry, &root, session.as_deref(), m),
+        ("new", Some(m)) => new_day(&root, m),
+        _ => unreachable!(),
+    }
+}
+
+const SESSION_HELP: &str = "The session cookie of adventofcode.com is read from \
+                            the AOC_SESSION environment variable or from the \
+                            file <root>/.session.";
+
+fn year_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("year")
+        .short("y")
+        .long("year")
+        .takes_value(true)
+        .value_name("YEAR")
+        .help("Year of the calendar [default: the latest year, or all \
+               years with --all]")
+}
+
+fn exit_with_error(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1);
+}
+
+fn exit_with_run_error(e: &RunError) -> ! {
+    eprintln!("{}", e);
+    std::process::exit(e.exit_code());
+}
+
+fn parse_year(matches: &ArgMatches) -> Option<u16> {
+    matches.value_of("year").map(|year| {
+        year.parse::<u16>()
+            .unwrap_or_else(|_| exit_with_error("Year must be numeric."))
+    })
+}
+
+fn parse_day(matches: &ArgMatches) -> Option<u8> {
+    matches.value_of("day").map(|day| {
+        day.parse::<u8>()
+            .unwrap_or_else(|_| exit_with_error("Day must be numeric."))
+    })
+}
+
+fn run(
+    registry: &Registry,
+    root: &Path,
+    inputs: &dyn InputProvider,
+    matches: &ArgMatches,
+) {
+    let format = if matches.is_present("json") {
+        OutputFormat::Json
+    } else {
+        OutputFormat::Human
+    };
+
+    if matches.is_present("all") {
+        let mut results = Vec::new();
+        let mut exit_code = 0;
+        for result in runner::run_all(registry, inputs, parse_year(matches)) {
+            match result {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    if exit_code == 0 { exit_code = e.exit_code(); }
+                },
+            }
+        }
+        let mut out = std::io::stdout();
+        match format {
+            OutputFormat::Human => runner::write_table(&mut out, &results),
+            OutputFormat::Json => runner::write_results(&mut out, &results, format),
+        }.expect("Could not write results");
+        std::process::exit(exit_code);
+    }
+
+    let year: u16 = parse_year(matches).unwrap_or_else(|| {
+        registry.days().last().map(|(year, _)| year).unwrap()
+    });
+    let day: u8 = parse_day(matches).unwrap();
+    let parts = match matches.value_of("part") {
+        Some(part) => vec![Part::from_number(part.parse::<u8>().unwrap()).unwrap()],
+        None => vec![Part::One, Part::Two],
+    };
+    let result = match (
+        matches.value_of("puzzle_input"),
+        matches.value_of("example")
+    ) {
+        (Some(arg), _) => runner::run(
+            registry, year, day, &parts, &InputSource::from_arg(arg)
+        ),
+        (None, Some(name)) => {
+            let example = examples::read_example(root, year, day, name)
+                .unwrap_or_else(|e| exit_with_error(&e.to_string()));
+            registry.get(year, day)
+                .ok_or(RunError::UnknownDay { year, day })
+                .and_then(|solution| {
+                    let source = InputSource::File(example.input);
+                    let puzzle_input = source.read()
+                        .map_err(|e| RunError::Input { input: source, source: e })?;
+                    runner::solve_with(solution, &puzzle_input, &example.params, &parts)
+                })
+        },
+        (None, None) => runner::run_day(registry, year, day, &parts, inputs),
+    };
+    match result {
+        Ok(result) => {
+            runner::write_results(&mut std::io::stdout(), &[result], format)
+                .expect("Could not write results");
+        },
+        Err(e) => exit_with_run_error(&e),
+    }
+}
+
+/// Exits with a non-zero code if any answer differs from the expected one
+/// or if a day could not be run at all.
+fn verify(
+    registry: &Registry,
+    root: &Path,
+    inputs: &dyn InputProvider,
+    matches: &ArgMatches,
+) {
+    let year = parse_year(matches);
+    let day = parse_day(matches);
+    let mut verdicts = Vec::new();
+    let mut num_errors: usize = 0;
+    for (y, d) in registry.days() {
+        if year.is_some_and(|year| year != y) { continue; }
+        if day.is_some_and(|day| day != d) { continue; }
+        let result = runner::run_day(registry, y, d, &[Part::One, Part::Two], inputs);
+        let expected = answers::read_answers(&answers::answers_path(root, y, d));
+        match (result, expected) {
+            (Ok(result), Ok(expected)) => {
+                verdicts.extend(verify::verify(&result, &expected));
+            },
+            (Err(e), _) => { eprintln!("{}", e); num_errors += 1; },
+            (_, Err(e)) => { eprintln!("{}", e); num_errors += 1; },
+        }
+    }
+    let num_failed = verify::write_report(&mut std::io::stdout(), &verdicts)
+        .expect("Could not write report");
+    if num_failed > 0 || num_errors > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn bench(registry: &Registry, inputs: &dyn InputProvider, matches: &ArgMatches) {
+    let samples = matches.value_of("samples").unwrap().parse::<usize>()
+        .ok().filter(|&n| n > 0)
+        .unwrap_or_else(|| exit_with_error("Samples must be a positive number."));
+    let budget = matches.value_of("budget").unwrap().parse::<f64>().ok()
+        .and_then(|secs| std::time::Duration::try_from_secs_f64(secs).ok())
+        .unwrap_or_else(|| exit_with_error("Budget must be a number of seconds."));
+    let baseline = matches.value_of("baseline").map(|path| {
+        bench::read_baseline(Path::new(path))
+            .unwrap_or_else(|e| exit_with_error(&e.to_string()))
+    });
+    let options = bench::BenchOptions { samples, budget };
+
+    let mut stats = Vec::new();
+    let mut exit_code = 0;
+    let results = bench::bench_all(
+        registry, inputs, parse_year(matches), parse_day(matches), &options
+    );
+    for result in results {
+        match result {
+            Ok(day_stats) => stats.extend(day_stats),
+            Err(e) => {
+                eprintln!("{}", e);
+                if exit_code == 0 { exit_code = e.exit_code(); }
+            },
+        }
+    }
+    let mut out = std::io::stdout();
+    if matches.is_present("json") {
+        bench::write_json(&mut out, &stats)
+    } else {
+        bench::write_table(&mut out, &stats, baseline.as_deref())
+    }.expect("Could not write results");
+    std::process::exit(exit_code);
+}
+
+fn seats(root: &Path, inputs: &dyn InputProvider, matches: &ArgMatches) {
+    use std::fs::File;
+    use std::io::{self, BufWriter, Write};
+    use std::time::Duration;
+    use aoc_2020::day11::{self, Day11, SeatEngine, SeatPosition, SeatRule};
+    use aoc_core::Solution;
+    use aoc_core::frames::{FrameRecorder, ImageFormat};
+
+    let (year, day) = (Day11::YEAR, Day11::DAY);
+    let layout = if let Some(size) = matches.value_of("generate") {
+        let (width, height) = size.split_once('x')
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            .unwrap_or_else(|| exit_with_error("The size must look like 500x500."));
+        let seed = matches.value_of("seed").unwrap().parse::<u64>()
+            .unwrap_or_else(|_| exit_with_error("The seed must be a number."));
+        day11::generate_layout(width, height, seed)
+    } else {
+        let puzzle_input = read_puzzle_input(root, inputs, matches, year, day);
+        Day11.parse(&puzzle_input).unwrap_or_else(|source| {
+            exit_with_run_error(&RunError::Solve { year, day, part: None, source })
+        })
+    };
+    let rule: SeatRule = matches.value_of("rule").unwrap().parse()
+        .unwrap_or_else(|e| exit_with_error(&format!("{}", e)));
+    let max_steps = matches.value_of("max_steps").unwrap().parse::<usize>()
+        .unwrap_or_else(|_| exit_with_error("Max steps must be a number."));
+
+    let delay = matches.value_of("delay").unwrap().parse::<u64>()
+        .unwrap_or_else(|_| exit_with_error("The delay must be a number."));
+    let scale = matches.value_of("scale").unwrap().parse::<usize>().ok()
+        .filter(|&scale| scale > 0)
+        .unwrap_or_else(|| exit_with_error("The scale must be a positive number."));
+    if matches.is_present("bench") {
+        bench_seats(&layout, &rule, max_steps);
+        return;
+    }
+
+    // Only keep a copy of every generation if it's going to be shown.
+    let record = ["animate", "frames", "images"].iter().any(|&arg| matches.is_present(arg));
+    let mut recorder = FrameRecorder::new();
+    let mut engine = SeatEngine::new(&layout, &rule);
+    let outcome = engine.run_observed(max_steps, |step, engine| {
+        if record {
+            recorder.record(step, &engine.layout());
+        }
+    });
+    if matches.is_present("animate") {
+        recorder.play(&mut io::stdout(), Duration::from_millis(delay))
+            .expect("Could not write to stdout");
+    }
+    if let Some(path) = matches.value_of("frames") {
+        let written = if path == "-" {
+            recorder.write_ascii(&mut io::stdout())
+        } else {
+            File::create(path).map(BufWriter::new).and_then(|mut out| {
+                recorder.write_ascii(&mut out)?;
+                out.flush()
+            })
+        };
+        written.unwrap_or_else(|e| exit_with_error(&format!("Could not write {}: {}", path, e)));
+    }
+    if let Some(dir) = matches.value_of("images") {
+        let format: ImageFormat = matches.value_of("format").unwrap().parse().unwrap();
+        let paths = recorder.write_images(Path::new(dir), format, scale, SeatPosition::color)
+            .unwrap_or_else(|e| exit_with_error(&format!("Could not write the images: {}", e)));
+        println!("Wrote {} images to {}", paths.len(), dir);
+    }
+    println!("Rule: {}", rule);
+    println!("Outcome: {}", outcome);
+    println!("Occupied seats at the end: {}", engine.count_occupied());
+}
+
+fn bench_seats(
+    layout: &aoc_core::Grid<aoc_2020::day11::SeatPosition>,
+    rule: &aoc_2020::day11::SeatRule,
+    max_steps: usize,
+) {
+    use std::time::Instant;
+    use aoc_2020::day11::{self, SeatEngine};
+
+    println!("Layout: {}x{}, rule: {}", layout.width(), layout.height(), rule);
+    let start = Instant::now();
+    let (outcome, expected) = day11::simulate(layout, rule, max_steps);
+    let full = start.elapsed();
+    println!("Full simulation:    {:>10.2?} ({})", full, outcome);
+
+    let start = Instant::now();
+    let mut engine = SeatEngine::new(layout, rule);
+    let engine_outcome = engine.run(max_steps);
+    let incremental = start.elapsed();
+    println!("Incremental engine: {:>10.2?} ({})", incremental, engine_outcome);
+    if engine_outcome != outcome || engine.layout() != expected {
+        exit_with_error("The engines disagree on the outcome.");
+    }
+    println!("Speedup: {:.1}x", full.as_secs_f64() / incremental.as_secs_f64());
+}
+
+/// Reads the input given by the `puzzle_input` or `example` arguments, or
+/// else the puzzle input of the day.
+fn read_puzzle_input(
+    root: &Path,
+    inputs: &dyn InputProvider,
+    matches: &ArgMatches,
+    year: u16,
+    day: u8,
+) -> String {
+    let read = |source: InputSource| source.read()
+        .map_err(|e| RunError::Input { input: source, source: e });
+    match (
+        matches.value_of("puzzle_input"),
+        matches.value_of("example")
+    ) {
+        (Some(arg), _) => read(InputSource::from_arg(arg)),
+        (None, Some(name)) => read(InputSource::File(input::example_path(root, year, day, name))),
+        (None, None) => inputs.input(year, day),
+    }.unwrap_or_else(|e| exit_with_run_error(&e))
+}
+
+fn bags(root: &Path, inputs: &dyn InputProvider, matches: &ArgMatches) {
+    use std::io::{self, Write};
+    use aoc_2020::day07::{Day07, Direction, Highlight, RuleSet};
+    use aoc_core::Solution;
+
+    let (year, day) = (Day07::YEAR, Day07::DAY);
+    let puzzle_input = read_puzzle_input(root, inputs, matches, year, day);
+    let rules: RuleSet = Day07.parse(&puzzle_input).unwrap_or_else(|source| {
+        exit_with_run_error(&RunError::Solve { year, day, part: None, source })
+    });
+    let color_id = |color: &str| rules.id(color)
+        .unwrap_or_else(|| exit_with_error(&format!("There are no \"{}\" bags.", color)));
+
+    let mut out = io::stdout().lock();
+    let written = match matches.subcommand() {
+        ("export", Some(m)) => {
+            let highlight = m.value_of("highlight").map(|color| Highlight {
+                color: color_id(color),
+                direction: m.value_of("direction").map_or(Direction::Both, |d| d.parse().unwrap()),
+                only: m.is_present("only"),
+            });
+            match m.value_of("format").unwrap() {
+                "dot" => rules.write_dot(&mut out, highlight.as_ref()),
+                _ => rules.write_json(&mut out, highlight.as_ref()),
+            }
+        },
+        ("paths", Some(m)) => {
+            let to = color_id(m.value_of("color").unwrap());
+            let from = m.value_of("from").map(color_id);
+            let limit = m.value_of("limit").unwrap().parse::<usize>()
+                .unwrap_or_else(|_| exit_with_error("The limit must be a number."));
+            let paths = rules.paths(from, to, limit).unwrap_or_else(|source| {
+                exit_with_run_error(&RunError::Solve { year, day, part: None, source })
+            });
+            paths.iter().try_for_each(|path| {
+                let colors: Vec<&str> = path.colors.iter().map(|&color| rules.color(color)).collect();
+                writeln!(out, "{}: {}", colors.join(" -> "), path.multiplicity)
+            }).and_then(|_| {
+                let total = rules.count_paths(from, to);
+                if total > paths.len() as u64 {
+                    writeln!(out, "{} of {} paths", paths.len(), total)
+                } else {
+                    writeln!(out, "{} paths", total)
+                }
+            })
+        },
+        ("breakdown", Some(m)) => {
+            let outer = color_id(m.value_of("color").unwrap());
+            let breakdown = rules.breakdown(outer).unwrap_or_else(|source| {
+                exit_with_run_error(&RunError::Solve { year, day, part: None, source })
+            });
+            breakdown.iter().try_for_each(|child| writeln!(
+                out, "{} {} with {} inside each: {}",
+                child.content.num, rules.color(child.content.color), child.nested, child.total
+            )).and_then(|_| {
+                writeln!(out, "Total: {}", breakdown.iter().map(|child| child.total).sum::<u64>())
+            })
+        },
+        ("deepest", Some(m)) => {
+            let chain = rules.deepest_chain(m.value_of("color").map(color_id));
+            let colors: Vec<&str> = chain.iter().map(|&color| rules.color(color)).collect();
+            writeln!(out, "{} levels: {}", chain.len().saturating_sub(1), colors.join(" -> "))
+        },
+        _ => unreachable!(),
+    };
+    written.and_then(|_| out.flush()).expect("Could not write to stdout");
+}
+
+const CONSOLE_HELP: &str = "\
+step [N]        execute the next N instructions (default 1)
+continue        run to the next breakpoint or until the program halts
+break PC|OP     pause before the instruction at PC, or before every nop/acc/jmp
+delete PC|OP    remove a breakpoint
+list [N]        show the N instructions around the pc (default 7)
+trace [N]       show the last N executed instructions (default 10)
+print           show the pc, the accumulator and the step count
+reset           start over, keeping the breakpoints
+quit            leave the debugger
+An empty line repeats the last command, and each command can be shortened to \
+its first letter.";
+
+fn console(root: &Path, inputs: &dyn InputProvider, matches: &ArgMatches) {
+    use std::io::{self, BufRead, Write};
+    use aoc_2020::day08::{Breakpoint, Day08, HaltPolicy, Program, Stop, Vm};
+    use aoc_core::Solution;
+
+    let (year, day) = (Day08::YEAR, Day08::DAY);
+    let puzzle_input = read_puzzle_input(root, inputs, matches, year, day);
+    let mut program: Program = Day08.parse(&puzzle_input).unwrap_or_else(|source| {
+        exit_with_run_error(&RunError::Solve { year, day, part: None, source })
+    });
+    if matches.is_present("fixed") {
+        let (fixed, _, i) = program.get_fixed_program().unwrap_or_else(|| {
+            exit_with_error("No single nop/jmp flip makes the program terminate.")
+        });
+        eprintln!("Flipped the instruction at pc {} to {}.", i, fixed.get(i).unwrap());
+        program = fixed;
+    }
+    let policy = HaltPolicy {
+        on_repeat: !matches.is_present("allow_repeats"),
+        max_steps: matches.value_of("max_steps").map(|n| n.parse().unwrap_or_else(|_| {
+            exit_with_error("Max steps must be a number.")
+        })),
+    };
+
+    let mut vm = Vm::new(&program).with_policy(policy).with_trace();
+    for spec in matches.values_of("break").into_iter().flatten() {
+        vm.add_breakpoint(spec.parse().unwrap_or_else(|e: String| exit_with_error(&e)));
+    }
+
+    let mut out = io::stdout().lock();
+    let show_state = |out: &mut io::StdoutLock, vm: &Vm| match vm.current() {
+        Some((pc, op)) => writeln!(out, "pc {}: {}    acc {}, {} steps", pc, op, vm.acc(), vm.steps()),
+        None => writeln!(out, "pc {}    acc {}, {} steps", vm.pc(), vm.acc(), vm.steps()),
+    };
+    let show_stop = |out: &mut io::StdoutLock, vm: &Vm, stop: Stop| {
+        match stop {
+            Stop::Breakpoint(b) => writeln!(out, "Breakpoint at {}", b)?,
+            Stop::Halted(halt) => writeln!(out, "Halted: {}", halt)?,
+        }
+        show_state(out, vm)
+    };
+
+    if matches.is_present("run") {
+        let written = (|| {
+            let stop = loop {
+                match vm.run() {
+                    Stop::Breakpoint(_) => continue,
+                    Stop::Halted(halt) => break Stop::Halted(halt),
+                }
+            };
+            vm.trace().unwrap().iter().try_for_each(|entry| writeln!(out, "{}", entry))?;
+            show_stop(&mut out, &vm, stop)
+        })();
+        written.and_then(|_| out.flush()).expect("Could not write to stdout");
+        return;
+    }
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut last = String::new();
+    let written: io::Result<()> = (|| {
+        show_state(&mut out, &vm)?;
+        loop {
+            write!(out, "(console) ")?;
+            out.flush()?;
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => return writeln!(out),
+            };
+            let line = if line.trim().is_empty() { last.clone() } else { line.trim().to_string() };
+            let mut words = line.split_whitespace();
+            let (command, arg) = (words.next().unwrap_or(""), words.next());
+            let count = |default: usize| arg.map_or(Ok(default), |n| n.parse::<usize>()
+                .map_err(|_| format!("Expected a number, not \"{}\"", n)));
+            // Invalid commands only get an error message, while failing to
+            // write to stdout ends the debugger.
+            let result: Result<io::Result<()>, String> = match command {
+                "" => Ok(Ok(())),
+                "s" | "step" => count(1).map(|n| {
+                    match (0..n).try_for_each(|_| vm.step().map(|_| ())) {
+                        Err(halt) => show_stop(&mut out, &vm, Stop::Halted(halt)),
+                        Ok(()) => show_state(&mut out, &vm),
+                    }
+                }),
+                "c" | "continue" => {
+                    let stop = vm.run();
+                    Ok(show_stop(&mut out, &vm, stop))
+                },
+                "b" | "break" | "d" | "delete" => match arg.map(str::parse::<Breakpoint>) {
+                    None => Err("Expected a pc or one of nop/acc/jmp".to_string()),
+                    Some(Err(e)) => Err(e),
+                    Some(Ok(b)) if command.starts_with('b') => {
+                        let added = vm.add_breakpoint(b);
+                        Ok(writeln!(out, "{} breakpoint at {}", if added { "Added" } else { "Already a" }, b))
+                    },
+                    Some(Ok(b)) => {
+                        let removed = vm.remove_breakpoint(b);
+                        Ok(writeln!(out, "{} breakpoint at {}", if removed { "Removed" } else { "No" }, b))
+                    },
+                },
+                "l" | "list" => count(7).map(|n| {
+                    let pc = vm.pc().clamp(0, program.len() as i64) as usize;
+                    let start = pc.saturating_sub(n / 2);
+                    (start..(start + n).min(program.len())).try_for_each(|i| {
+                        let marker = if i == pc { "=>" } else { "  " };
+                        let op = program.get(i).unwrap();
+                        writeln!(out, "{} {:>4}: {:<10} {} visits", marker, i, op, vm.visits(i))
+                    })
+                }),
+                "t" | "trace" => count(10).map(|n| {
+                    let trace = vm.trace().unwrap();
+                    trace[trace.len().saturating_sub(n)..].iter()
+                        .try_for_each(|entry| writeln!(out, "{}", entry))
+                }),
+                "p" | "print" => Ok(show_state(&mut out, &vm)),
+                "r" | "reset" => {
+                    vm.reset();
+                    Ok(show_state(&mut out, &vm))
+                },
+                "h" | "help" => Ok(writeln!(out, "{}", CONSOLE_HELP)),
+                "q" | "quit" => return Ok(()),
+                _ => Err(format!("Unknown command \"{}\", try \"help\"", command)),
+            };
+            match result {
+                Ok(written) => written?,
+                Err(msg) => writeln!(out, "{}", msg)?,
+            }
+            last = line;
+        }
+    })();
+    written.and_then(|_| out.flush()).expect("Could not write to stdout");
+}
+
+fn require_session(session: Option<&str>) -> &str {
+    session.unwrap_or_else(|| exit_with_error(&format!(
+        "No session cookie. Set {} or write it to <root>/.session.", fetch::SESSION_VAR
+    )))
+}
+
+fn fetch_input(
+    registry: &Registry,
+    root: &Path,
+    session: Option<&str>,
+    matches: &ArgMatches,
+) {
+    let year: u16 = parse_year(matches).unwrap_or_else(|| {
+        registry.days().last().map(|(year, _)| year).unwrap()
+    });
+    let day: u8 = parse_day(matches).unwrap();
+    let inputs = CachedInputs::new(root, HttpFetcher::new(require_session(session)));
+    if let Err(e) = inputs.input(year, day) {
+        exit_with_run_error(&e);
+    }
+    println!("{}", input::input_path(root, year, day).display());
+}
+
+/// Exits with a non-zero code unless the answer is correct.
+fn submit(
+    registry: &Registry,
+    root: &Path,
+    session: Option<&str>,
+    matches: &ArgMatches,
+) {
+    let year: u16 = parse_year(matches).unwrap_or_else(|| {
+        registry.days().last().map(|(year, _)| year).unwrap()
+    });
+    let day: u8 = parse_day(matches).unwrap();
+    let part = Part::from_number(matches.value_of("part").unwrap().parse().unwrap()).unwrap();
+    let fetcher = HttpFetcher::new(require_session(session));
+    let answer = match matches.value_of("answer") {
+        Some(answer) => answer.to_string(),
+        None => {
+            let inputs = CachedInputs::new(root, &fetcher);
+            let result = runner::run_day(registry, year, day, &[part], &inputs)
+                .unwrap_or_else(|e| exit_with_run_error(&e));
+            if result.parts[0].answer == Answer::Unsolved {
+                exit_with_error(&format!("Part {} is not solved yet.", part));
+            }
+            result.parts[0].answer.to_string()
+        },
+    };
+    match fetch::submit(&fetcher, root, year, day, part, &answer) {
+        Ok((submission, known)) => {
+            let known = if known { " (known from earlier submissions)" } else { "" };
+            println!("{} day {} part {}: {} is {}{}", year, day, part, answer, submission, known);
+            if submission != fetch::Submission::Correct {
+                std::process::exit(1);
+            }
+        },
+        Err(e) => exit_with_error(&e.to_string()),
+    }
+}
+
+fn new_day(root: &Path, matches: &ArgMatches) {
+    let year = parse_year(matches).unwrap();
+    let day = parse_day(matches).unwrap();
+    if !(1..=25).contains(&day) {
+        exit_with_error("Day must be between 1 and 25.");
+    }
+    let changed = scaffold::scaffold(root, year, day)
+        .unwrap_or_else(|e| exit_with_error(&format!("{}.", e)));
+    for path in changed {
+        println!("{}", path.display());
+    }
+    println!(
+        "Fill in example1.txt and example1.toml, then run `cargo test -p aoc-{}`.",
+        year
+    );
+}